@@ -1,8 +1,12 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
+#[command(subcommand_negates_reqs = true)]
 pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// Disable top generator
     #[arg(long, default_value = "false")]
     pub no_top: bool,
@@ -12,6 +16,9 @@ pub struct Args {
     /// Disable self-test generator
     #[arg(long, default_value = "false")]
     pub no_self_test: bool,
+    /// Abort generation on compatibility mismatches
+    #[arg(long, default_value = "false")]
+    pub strict: bool,
 
     /// Relative path to common config file
     #[arg(short, long, default_value = "./project.toml")]
@@ -30,3 +37,21 @@ pub struct Args {
     #[arg(required = true)]
     pub vips: Vec<String>,
 }
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Inspect DUT file
+    Dut {
+        #[command(subcommand)]
+        command: DutCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum DutCommand {
+    /// List modules and their ports found in DUT file
+    List {
+        /// Relative path to DUT file (defaults to project dut path)
+        path: Option<String>,
+    },
+}
@@ -1,9 +1,13 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
-use crate::render::vip::VIP;
+use crate::dut::connection::{ConnectionExpr, ConnectionTerm};
+use crate::dut::utils::{fmt_suggestion, get_closest_name, PortDirection, DUT};
+use crate::render::parameter::get_specialization;
+use crate::render::vip::{VIPPort, VIP};
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
 #[serde(deny_unknown_fields)]
@@ -21,20 +25,28 @@ pub fn get_self_test_instances(vip: &VIP) -> Instances {
     let modes = vec![Mode::Controller, Mode::Passive, Mode::Responder];
     let mut instances = Vec::new();
     let mut connected_to = Vec::new();
+    let mut connections = Vec::new();
     for p in &vip.ports {
         connected_to.push(p.name.clone());
+        connections.push(Connection::new(p.name.clone(), p.name.clone()));
     }
     for m in modes {
         let instance = Instance {
             vip_name: vip.name.clone(),
             connected_to: connected_to.clone(),
+            connect: BTreeMap::new(),
             id: Some(0),
             mode: m,
+            parameters: BTreeMap::new(),
+            connections: connections.clone(),
+            specialization: String::new(),
         };
         instances.push(instance);
     }
 
-    instances.into()
+    let mut instances: Instances = instances.into();
+    instances.set_specializations(std::slice::from_ref(vip));
+    instances
 }
 
 impl Instances {
@@ -102,6 +114,79 @@ impl Instances {
             }
         }
     }
+
+    pub fn set_connections(&mut self, vips: &[VIP], strict: bool) {
+        info!("resolving instances connections");
+        let mut vip_ports = HashMap::new();
+        for v in vips {
+            let ports: Vec<String> = v.ports.iter().map(|p| p.name.clone()).collect();
+            vip_ports.insert(v.name.clone(), ports);
+        }
+        let mut errors = 0;
+
+        for i in &mut self.instances {
+            match vip_ports.get(&i.vip_name) {
+                Some(ports) => {
+                    for e in i.set_connections(ports) {
+                        if let ConnectError::UnconnectedPort { .. } = e {
+                            warn!("{}", e);
+                        } else {
+                            error!("{}", e);
+                            errors += 1;
+                        }
+                    }
+                }
+                None => debug!("pass connections of unknown vip {}", i.vip_name),
+            }
+        }
+
+        if strict && errors > 0 {
+            panic!("{} instances connections errors in strict mode", errors);
+        }
+    }
+
+    pub fn set_specializations(&mut self, vips: &[VIP]) {
+        info!("resolving instances parameters");
+        for i in &mut self.instances {
+            let is_parameterized = vips
+                .iter()
+                .any(|v| v.name == i.vip_name && !v.parameters.is_empty());
+            i.specialization =
+                get_specialization(i.parameters.iter().map(|(n, v)| (n, v.to_string())));
+            // default specialization, the unadorned name cannot prefix '::'
+            if is_parameterized && i.specialization.is_empty() {
+                i.specialization = " #()".to_string();
+            }
+            debug!("{} specialized with '{}'", i, i.specialization);
+        }
+    }
+
+    pub fn get_dut_connections(&self) -> Vec<String> {
+        self.instances
+            .iter()
+            .flat_map(|i| i.connections.iter().map(|c| c.dut_port.clone()))
+            .collect()
+    }
+
+    pub fn set_harness_assigns(&mut self, vips: &[VIP], dut: &DUT) {
+        info!("resolving instances harness assigns");
+        for i in &mut self.instances {
+            // invalid parameters are reported while checking (instances vip) <-> DUT compatibility
+            let ports: Vec<VIPPort> = match vips.iter().find(|v| v.name == i.vip_name) {
+                Some(v) => v
+                    .get_instance_ports(&i.parameters)
+                    .unwrap_or_else(|_| v.ports.clone()),
+                None => Vec::new(),
+            };
+            for c in &mut i.connections {
+                let vip_port = ports.iter().find(|p| p.name == c.vip_port);
+                match vip_port {
+                    Some(vip_port) => c.set_harness_assigns(vip_port, dut),
+                    None => debug!("pass assigns of unknown port {}", c.vip_port),
+                }
+            }
+        }
+    }
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
@@ -110,9 +195,153 @@ pub struct Instance {
     pub vip_name: String,
     #[serde(default)]
     pub connected_to: Vec<String>,
+    #[serde(default)]
+    pub connect: BTreeMap<String, String>,
     pub id: Option<u32>,
     #[serde(default)]
     pub mode: Mode,
+    // values of the VIP parameters, defaults otherwise
+    #[serde(default)]
+    pub parameters: BTreeMap<String, u32>,
+
+    #[serde(default, skip_deserializing)]
+    pub connections: Vec<Connection>,
+    // parameter assignments of the specialized VIP types, e.g. ' #(.DATA_W(32))'
+    #[serde(default, skip_deserializing)]
+    pub specialization: String,
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ConnectError {
+    #[error("both connected_to and connect found in {0}, only connect is used")]
+    ConnectAndConnectedTo(String),
+
+    #[error(
+        "found more connected ports than declared in {instance}\ngot {got} expected {expected}"
+    )]
+    TooManyConnections {
+        instance: String,
+        got: usize,
+        expected: usize,
+    },
+
+    #[error("{port} in {instance} is not a port of vip {vip}{}", fmt_suggestion(.suggestion))]
+    UnknownPort {
+        port: String,
+        instance: String,
+        vip: String,
+        suggestion: Option<String>,
+    },
+
+    #[error("{vip} port {port} is not connected in {instance}{}", fmt_suggestion(.suggestion))]
+    UnconnectedPort {
+        port: String,
+        instance: String,
+        vip: String,
+        suggestion: Option<String>,
+    },
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Connection {
+    pub vip_port: String,
+    pub dut_port: String,
+
+    pub is_direct: bool,
+    pub assigns: Vec<HarnessAssign>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct HarnessAssign {
+    pub dut_expr: String,
+    pub vip_slice: String,
+    pub to_dut: bool,
+}
+
+impl Connection {
+    fn new(vip_port: String, dut_port: String) -> Self {
+        Connection {
+            vip_port,
+            dut_port,
+            is_direct: true,
+            assigns: Vec::new(),
+        }
+    }
+
+    fn set_harness_assigns(&mut self, vip_port: &VIPPort, dut: &DUT) {
+        self.is_direct = false;
+        self.assigns.clear();
+
+        // errors are reported while checking (instances vip) <-> DUT compatibility
+        let expr = match self.dut_port.parse::<ConnectionExpr>() {
+            Ok(expr) => expr,
+            Err(_) => return,
+        };
+        if expr.is_whole_port() {
+            self.is_direct = true;
+            return;
+        }
+        let terms = match expr.resolve(dut) {
+            Ok(terms) => terms,
+            Err(_) => return,
+        };
+
+        let vip_width = vip_port.properties.get_width();
+        let mut offset = match vip_port.properties.dimensions.first() {
+            Some((end, start)) => *end.min(start),
+            None => 0,
+        };
+        let has_input = terms
+            .iter()
+            .any(|t| t.direction == Some(PortDirection::INPUT));
+
+        for t in terms.iter().rev() {
+            let width = t.width.unwrap_or(vip_width);
+            let vip_slice = if terms.len() == 1 {
+                "".to_string()
+            } else if width == 1 {
+                format!("[{}]", offset)
+            } else {
+                format!("[{}:{}]", offset + width - 1, offset)
+            };
+            offset += width;
+
+            let to_dut = match (&t.term, &t.direction) {
+                (ConnectionTerm::Constant { value: _, width: _ }, _) => {
+                    if has_input {
+                        debug!(
+                            "constant {} ignored, {} drives the dut",
+                            t.term, self.vip_port
+                        );
+                        continue;
+                    }
+                    false
+                }
+                (_, Some(PortDirection::INPUT)) => true,
+                (_, Some(PortDirection::INOUT)) => {
+                    warn!(
+                        "inout {} connected as an output to {}{}",
+                        t.term, self.vip_port, vip_slice
+                    );
+                    false
+                }
+                (_, _) => false,
+            };
+
+            debug!(
+                "{}{} assigned {} {}",
+                self.vip_port,
+                vip_slice,
+                if to_dut { "to" } else { "from" },
+                t.term
+            );
+            self.assigns.push(HarnessAssign {
+                dut_expr: t.term.to_string(),
+                vip_slice,
+                to_dut,
+            });
+        }
+    }
 }
 
 impl std::fmt::Display for Instance {
@@ -128,6 +357,81 @@ impl std::fmt::Display for Instance {
 }
 
 impl Instance {
+    // connections by name when connect is given, by position otherwise
+    fn set_connections(&mut self, vip_ports: &[String]) -> Vec<ConnectError> {
+        let mut errors = Vec::new();
+        let unconnected =
+            |port: &String, suggestion: Option<String>| ConnectError::UnconnectedPort {
+                port: port.clone(),
+                instance: self.to_string(),
+                vip: self.vip_name.clone(),
+                suggestion,
+            };
+
+        let connections: Vec<Connection> = if self.connect.is_empty() {
+            if self.connected_to.len() > vip_ports.len() {
+                errors.push(ConnectError::TooManyConnections {
+                    instance: self.to_string(),
+                    got: self.connected_to.len(),
+                    expected: vip_ports.len(),
+                });
+            }
+            for vp in vip_ports.iter().skip(self.connected_to.len()) {
+                errors.push(unconnected(vp, None));
+            }
+
+            vip_ports
+                .iter()
+                .zip(&self.connected_to)
+                .map(|(vp, dp)| Connection::new(vp.clone(), dp.clone()))
+                .collect()
+        } else {
+            if !self.connected_to.is_empty() {
+                errors.push(ConnectError::ConnectAndConnectedTo(self.to_string()));
+            }
+            // unknown keys and their closest VIP port
+            let mut misspelled = Vec::new();
+            for vp in self.connect.keys().filter(|vp| !vip_ports.contains(vp)) {
+                let suggestion = get_closest_name(vp, vip_ports);
+                misspelled.push((vp.clone(), suggestion.clone()));
+                errors.push(ConnectError::UnknownPort {
+                    port: vp.clone(),
+                    instance: self.to_string(),
+                    vip: self.vip_name.clone(),
+                    suggestion,
+                });
+            }
+            for vp in vip_ports
+                .iter()
+                .filter(|vp| !self.connect.contains_key(*vp))
+            {
+                let suggestion = misspelled
+                    .iter()
+                    .find(|(_key, closest)| closest.as_ref() == Some(vp))
+                    .map(|(key, _closest)| key.clone());
+                errors.push(unconnected(vp, suggestion));
+            }
+
+            vip_ports
+                .iter()
+                .filter_map(|vp| {
+                    self.connect
+                        .get(vp)
+                        .map(|dp| Connection::new(vp.clone(), dp.clone()))
+                })
+                .collect()
+        };
+        self.connections = connections;
+
+        for c in &self.connections {
+            debug!(
+                "{} port {} mapped to {}",
+                self.vip_name, c.vip_port, c.dut_port
+            );
+        }
+        errors
+    }
+
     fn set_next_available_id(
         &mut self,
         counts: &mut HashMap<String, HashMap<Mode, u32>>,
@@ -188,3 +492,92 @@ pub enum Mode {
     Responder,
     Passive,
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{ConnectError, Instance, Mode};
+
+    fn instance(connected_to: Vec<&str>, connect: Vec<(&str, &str)>) -> Instance {
+        Instance {
+            vip_name: "fifo".to_string(),
+            connected_to: connected_to.into_iter().map(|p| p.to_string()).collect(),
+            connect: connect
+                .into_iter()
+                .map(|(vp, dp)| (vp.to_string(), dp.to_string()))
+                .collect(),
+            id: Some(0),
+            mode: Mode::Controller,
+            parameters: BTreeMap::new(),
+            connections: Vec::new(),
+            specialization: String::new(),
+        }
+    }
+
+    fn connections(instance: &Instance) -> Vec<(&str, &str)> {
+        instance
+            .connections
+            .iter()
+            .map(|c| (c.vip_port.as_str(), c.dut_port.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn instance_connections() {
+        let vip_ports = vec!["data".to_string(), "valid".to_string(), "ready".to_string()];
+
+        let mut positional = instance(vec!["din", "din_vld", "din_rdy"], vec![]);
+        assert_eq!(positional.set_connections(&vip_ports), vec![]);
+        assert_eq!(
+            connections(&positional),
+            vec![("data", "din"), ("valid", "din_vld"), ("ready", "din_rdy")]
+        );
+
+        let mut by_name = instance(vec![], vec![("ready", "din_rdy"), ("data", "din")]);
+        let errors = by_name.set_connections(&vip_ports);
+        assert_eq!(
+            connections(&by_name),
+            vec![("data", "din"), ("ready", "din_rdy")]
+        );
+        assert!(matches!(
+            &errors[..],
+            [ConnectError::UnconnectedPort { port, suggestion: None, .. }] if port == "valid"
+        ));
+
+        let mut misspelled = instance(vec!["din"], vec![("data", "din"), ("vaild", "din_vld")]);
+        let errors = misspelled.set_connections(&vip_ports);
+        assert_eq!(connections(&misspelled), vec![("data", "din")]);
+        assert!(matches!(errors[0], ConnectError::ConnectAndConnectedTo(_)));
+        assert!(matches!(
+            &errors[1],
+            ConnectError::UnknownPort { port, suggestion: Some(s), .. } if port == "vaild" && s == "valid"
+        ));
+        assert!(matches!(
+            &errors[2],
+            ConnectError::UnconnectedPort { port, suggestion: Some(s), .. } if port == "valid" && s == "vaild"
+        ));
+        assert!(matches!(
+            &errors[3],
+            ConnectError::UnconnectedPort { port, suggestion: None, .. } if port == "ready"
+        ));
+        assert_eq!(errors.len(), 4);
+
+        let mut too_many = instance(vec!["din", "din_vld", "din_rdy", "din_err"], vec![]);
+        let errors = too_many.set_connections(&vip_ports);
+        assert_eq!(connections(&too_many).len(), 3);
+        assert!(matches!(
+            errors[..],
+            [ConnectError::TooManyConnections {
+                got: 4,
+                expected: 3,
+                ..
+            }]
+        ));
+
+        let mut missing = instance(vec!["din"], vec![]);
+        let errors = missing.set_connections(&vip_ports);
+        assert_eq!(connections(&missing), vec![("data", "din")]);
+        assert_eq!(errors.len(), 2);
+    }
+}
@@ -2,19 +2,24 @@ pub mod instance;
 pub mod project;
 pub mod vip;
 
-use std::cmp::Ordering;
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 
 use log::{debug, error, info, trace, warn};
 
 use instance::Instances;
 use project::Project;
-use vip::VIP;
+use vip::VIP as VIPcfg;
 
 use crate::cli::Args;
-use crate::dut::utils::DUT;
+use crate::config::project::DUT as DUTcfg;
+use crate::dut::connection::{
+    parse_constant, ConnectionExpr, ConnectionTerm, ResolveConnectionError,
+};
+use crate::dut::utils::{fmt_suggestion, get_closest_name, Port, PortDirection, DUT};
+use crate::render::top::{get_dut_clk_rst_ports, get_unconnected_ports};
+use crate::render::vip::VIP;
 
-pub fn parse_config_files(cli: &Args) -> (Project, Instances, Vec<VIP>) {
+pub fn parse_config_files(cli: &Args) -> (Project, Instances, Vec<VIPcfg>) {
     let project = parse_project_file(cli.project.clone());
 
     let instances = parse_instances_file(cli.instances.clone());
@@ -31,7 +36,7 @@ pub fn parse_project_file(path: String) -> Project {
         error!("{}", e);
         panic!("bad project file {}", path);
     });
-    if project.dut.name == None {
+    if project.dut.name.is_none() {
         let name = get_name_from_file_path(project.dut.path.clone());
         debug!("default dut name to {}", name);
         project.dut.name = Some(name);
@@ -53,17 +58,17 @@ pub fn parse_instances_file(path: String) -> Instances {
     instances
 }
 
-pub fn parse_vip_files(paths: &Vec<String>) -> Vec<VIP> {
+pub fn parse_vip_files(paths: &Vec<String>) -> Vec<VIPcfg> {
     info!("reading vip templates");
     let mut vips = Vec::new();
     for path in paths {
         info!("reading vip template {}", path);
-        let vip_str = std::fs::read_to_string(&path).unwrap();
-        let mut vip: VIP = toml::from_str(&vip_str).unwrap_or_else(|e| {
+        let vip_str = std::fs::read_to_string(path).unwrap();
+        let mut vip: VIPcfg = toml::from_str(&vip_str).unwrap_or_else(|e| {
             error!("{}", e);
             panic!("bad vip file {}", path);
         });
-        if vip.name == None {
+        if vip.name.is_none() {
             let name = get_name_from_file_path(path.clone());
             debug!("default vip name to {}", name);
             vip.name = Some(name);
@@ -87,41 +92,36 @@ fn get_name_from_file_path(file_path: String) -> String {
     } else {
         file_name
     };
-    let name = name.to_string();
 
-    name
+    name.to_string()
 }
 
-pub fn check_i_v_compat(instances: &Instances, vips: &Vec<VIP>) {
-    info!("checking instances <-> vip ports compatibility");
-    let mut vip_ports = HashMap::new();
+pub fn check_i_v_compat(instances: &Instances, vips: &[VIP], strict: bool) {
+    // connections are checked while resolving them
+    info!("checking instances <-> vip parameters compatibility");
+    let mut vip_parameters = HashMap::new();
     for v in vips {
-        vip_ports.insert(v.name.clone().unwrap(), &v.ports);
+        let parameters: Vec<String> = v.parameters.iter().map(|p| p.name.clone()).collect();
+        vip_parameters.insert(v.name.clone(), parameters);
     }
+    let mut errors = 0;
+
     for i in &instances.instances {
-        match vip_ports.get(&i.vip_name) {
-            Some(ports) => {
+        match vip_parameters.get(&i.vip_name) {
+            Some(parameters) => {
                 debug!("instanciate vip {}", i.vip_name);
 
-                match ports.len().cmp(&i.connected_to.len()) {
-                    Ordering::Equal => {
-                        debug!("all ports connected {}", i);
-                    }
-                    Ordering::Greater => {
-                        warn!(
-                            "found less connected ports than declared in {}\ngot {} expected {}",
-                            i,
-                            i.connected_to.len(),
-                            ports.len()
-                        );
-                    }
-                    Ordering::Less => {
+                for p in i.parameters.keys() {
+                    if !parameters.contains(p) {
+                        let suggestion = get_closest_name(p, parameters);
                         error!(
-                            "found more connected ports than declared in {}\ngot {} expected {}",
+                            "{} in {} is not a parameter of vip {}{}",
+                            p,
                             i,
-                            i.connected_to.len(),
-                            ports.len()
+                            i.vip_name,
+                            fmt_suggestion(&suggestion)
                         );
+                        errors += 1;
                     }
                 }
             }
@@ -130,24 +130,269 @@ pub fn check_i_v_compat(instances: &Instances, vips: &Vec<VIP>) {
             }
         }
     }
+
+    if strict && errors > 0 {
+        panic!(
+            "{} instances <-> vip parameters compatibility errors in strict mode",
+            errors
+        );
+    }
 }
 
-pub fn check_i_v_d_compat(instances: &Instances, _vips: &Vec<VIP>, dut: &DUT) {
-    // #TODO add dimensions checks using vips info
+pub fn check_i_v_d_compat(instances: &Instances, vips: &[VIP], dut: &DUT, strict: bool) {
     info!("checking (instances vip) <-> DUT ports compatibility");
-    let mut connected = HashSet::new();
+    let dut_names: Vec<String> = dut.ports.keys().cloned().collect();
+    let mut connected = HashMap::<String, Vec<(u32, u32, String)>>::new();
+    let mut errors = 0;
 
     for i in &instances.instances {
-        for p in &i.connected_to {
-            if dut.ports.contains_key(p) {
-                if connected.insert(p) {
-                    debug!("{} connected succefully by {}", p, i);
-                } else {
-                    error!("{} is already connected by {}", p, i);
+        // ports sized with the instance parameters
+        let ports = match vips.iter().find(|v| v.name == i.vip_name) {
+            Some(v) => match v.get_instance_ports(&i.parameters) {
+                Ok(ports) => ports,
+                Err(e) => {
+                    error!("invalid parameters in {}: {}", i, e);
+                    errors += 1;
+                    v.ports.clone()
+                }
+            },
+            None => Vec::new(),
+        };
+        for c in &i.connections {
+            let expr = match c.dut_port.parse::<ConnectionExpr>() {
+                Ok(expr) => expr,
+                Err(e) => {
+                    error!("{} in {} is not a valid connection: {}", c.dut_port, i, e);
+                    errors += 1;
+                    continue;
                 }
+            };
+            let terms = match expr.resolve(dut) {
+                Ok(terms) => terms,
+                Err(ResolveConnectionError::UnknownPort(p)) => {
+                    let suggestion = get_closest_name(&p, &dut_names);
+                    error!(
+                        "{} in {} does not exist in dut {}{}",
+                        p,
+                        i,
+                        dut.name,
+                        fmt_suggestion(&suggestion)
+                    );
+                    errors += 1;
+                    continue;
+                }
+                Err(e) => {
+                    error!("{} in {} of dut {}", e, i, dut.name);
+                    errors += 1;
+                    continue;
+                }
+            };
+
+            for t in &terms {
+                if let (ConnectionTerm::Port { name, range: _ }, Some((low, high))) =
+                    (&t.term, t.bits)
+                {
+                    let owners = connected.entry(name.clone()).or_default();
+                    let owner = owners
+                        .iter()
+                        .find(|(owned_low, owned_high, _owner)| {
+                            low <= *owned_high && *owned_low <= high
+                        })
+                        .map(|(_low, _high, owner)| owner);
+                    if let Some(owner) = owner {
+                        error!("{} in {} is already connected by {}", t.term, i, owner);
+                        errors += 1;
+                    } else {
+                        debug!("{} connected succefully by {}", t.term, i);
+                        owners.push((low, high, i.to_string()));
+                    }
+                }
+            }
+
+            let vip_port = match ports.iter().find(|p| p.name == c.vip_port) {
+                Some(vip_port) => vip_port,
+                None => continue,
+            };
+            let vip_width = vip_port.properties.get_width();
+            let dut_width = match terms.iter().map(|t| t.width).sum::<Option<u32>>() {
+                Some(dut_width) => dut_width,
+                None => {
+                    debug!("{} sized by {} port {}", expr, i.vip_name, vip_port);
+                    continue;
+                }
+            };
+            // in strict mode a whole port connection also needs the same dimensions
+            let whole_port = expr
+                .get_whole_port()
+                .and_then(|name| dut.ports.get_key_value(name));
+            let same_dimensions = !strict
+                || whole_port.is_none_or(|(_, properties)| {
+                    vip_port.properties.has_same_dimensions(properties)
+                });
+            if vip_width == dut_width && same_dimensions {
+                debug!(
+                    "{} port {} and dut {} widths matching ({} bits)",
+                    i.vip_name, vip_port.name, expr, vip_width
+                );
             } else {
-                error!("{} in {} does not exist in dut {}", p, i, dut.name);
+                let dut_connection = if let Some((name, properties)) = whole_port {
+                    format!(
+                        "port {}",
+                        Port {
+                            name: name.clone(),
+                            properties: properties.clone(),
+                        }
+                    )
+                } else {
+                    format!("connection {}", expr)
+                };
+                let mismatch = if vip_width == dut_width {
+                    format!(
+                        "{} port {} dimensions do not match dut {} in {}",
+                        i.vip_name, vip_port, dut_connection, i
+                    )
+                } else {
+                    format!(
+                        "{} port {} ({} bits) does not match dut {} ({} bits) in {}",
+                        i.vip_name, vip_port, vip_width, dut_connection, dut_width, i
+                    )
+                };
+                if strict {
+                    error!("{}", mismatch);
+                    errors += 1;
+                } else {
+                    warn!("{}", mismatch);
+                }
             }
         }
     }
+
+    if strict && errors > 0 {
+        panic!(
+            "{} (instances vip) <-> DUT ports compatibility errors in strict mode",
+            errors
+        );
+    }
+}
+
+pub fn check_d_connected(instances: &Instances, vips: &[VIP], dut: &DUT, dut_cfg: &DUTcfg) {
+    info!("checking unconnected DUT ports");
+    let dut_names: Vec<String> = dut.ports.keys().cloned().collect();
+
+    for (p, value) in &dut_cfg.tie_offs {
+        let width = match parse_constant(value.trim()) {
+            Ok(ConnectionTerm::Constant { value: _, width }) => width,
+            Ok(_) | Err(_) => {
+                error!(
+                    "tie-off {} of dut port {} is not a constant (expected: '<u32>'<base><value>', '<u32>' or unsized ''0'/''1')",
+                    value, p
+                );
+                panic!("bad dut tie-off {}", p);
+            }
+        };
+        match dut.ports.get(p) {
+            Some(properties) => {
+                if properties.direction != PortDirection::INPUT {
+                    warn!(
+                        "tie-off {} of dut {} {} ignored",
+                        value, properties.direction, p
+                    );
+                } else if width.is_some_and(|w| w != properties.get_width()) {
+                    warn!(
+                        "tie-off {} does not match dut input {} ({} bits)",
+                        value,
+                        Port {
+                            name: p.clone(),
+                            properties: properties.clone(),
+                        },
+                        properties.get_width()
+                    );
+                }
+            }
+            None => {
+                let suggestion = get_closest_name(p, &dut_names);
+                warn!(
+                    "tie-off port {} does not exist in dut {}{}",
+                    p,
+                    dut.name,
+                    fmt_suggestion(&suggestion)
+                );
+            }
+        }
+    }
+
+    for c in get_dut_clk_rst_ports(instances, vips, dut, dut_cfg) {
+        info!("dut input {} driven by harness {}", c.port, c.signal);
+    }
+
+    for u in get_unconnected_ports(instances, vips, dut, dut_cfg) {
+        let properties = &u.port.properties;
+        let bits = if u.is_whole_port {
+            "".to_string()
+        } else {
+            let bits: Vec<String> = u
+                .ranges
+                .iter()
+                .map(|(low, high)| format!("[{}:{}]", high, low))
+                .collect();
+            format!(" bits {}", bits.join(""))
+        };
+        let resolution = match properties.direction {
+            PortDirection::INPUT => format!(
+                "tied off to {}",
+                dut_cfg.tie_offs.get(&u.port.name).map_or("'0", |v| v)
+            ),
+            _ => "left open".to_string(),
+        };
+
+        warn!(
+            "dut {} {}{} not connected by any instance, {}",
+            properties.direction, u.port, bits, resolution
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::check_i_v_d_compat;
+    use crate::config::instance::Instances;
+    use crate::config::vip::VIP as VIPcfg;
+    use crate::dut::utils::{Port, DUT};
+    use crate::render::vip::{get_render_vips, VIP};
+
+    #[test]
+    fn padded_dut_port_names() {
+        let vip: VIPcfg = toml::from_str(
+            r#"
+            name = "fifo_16"
+            ports = ["data [15:0]", "data_vld"]
+            "#,
+        )
+        .unwrap();
+        let vips: Vec<VIP> = get_render_vips(&vec![vip]);
+        let mut instances: Instances = toml::from_str(
+            r#"
+            [[instances]]
+            vip_name = "fifo_16"
+            connected_to = [" data_in ", "data_in_vld "]
+            "#,
+        )
+        .unwrap();
+        instances.estimate_ids();
+        instances.set_connections(&vips, true);
+        let mut ports = HashMap::new();
+        for description in ["data_in [15:0]", "data_in_vld"] {
+            let port = description.parse::<Port>().unwrap();
+            ports.insert(port.name, port.properties);
+        }
+        let dut = DUT {
+            name: "fifo".to_string(),
+            ports,
+        };
+
+        // whole port connections are looked up without the padding
+        check_i_v_d_compat(&instances, &vips, &dut, true);
+    }
 }
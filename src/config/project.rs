@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -8,6 +10,9 @@ pub struct Project {
     #[serde(default = "default_top_default_sequence")]
     pub top_default_sequence: u32,
 
+    #[serde(default)]
+    pub timescale: Timescale,
+
     #[serde(default = "default_dut")]
     pub dut: DUT,
 }
@@ -18,6 +23,7 @@ fn default_dut() -> DUT {
         name: None,
         clock: None,
         reset: None,
+        tie_offs: BTreeMap::new(),
     }
 }
 fn default_generate_file_header() -> bool {
@@ -35,8 +41,35 @@ pub struct DUT {
     pub name: Option<String>,
     pub clock: Option<String>,
     pub reset: Option<String>,
+    #[serde(default)]
+    pub tie_offs: BTreeMap<String, String>,
 }
 
 fn default_path() -> String {
     "dut.sv".to_string()
 }
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Timescale {
+    #[serde(default = "default_timeunit")]
+    pub unit: String,
+    #[serde(default = "default_timeprecision")]
+    pub precision: String,
+}
+
+impl Default for Timescale {
+    fn default() -> Self {
+        Timescale {
+            unit: default_timeunit(),
+            precision: default_timeprecision(),
+        }
+    }
+}
+
+fn default_timeunit() -> String {
+    "1ns".to_string()
+}
+fn default_timeprecision() -> String {
+    "1ps".to_string()
+}
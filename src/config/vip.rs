@@ -1,26 +1,318 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Serialize, Debug)]
 #[serde(deny_unknown_fields)]
 pub struct VIP {
     pub name: Option<String>,
-    pub ports: Vec<String>,
+    // integer parameters of the interface and classes, usable in port and member widths
+    #[serde(default)]
+    pub parameters: Vec<Parameter>,
+    pub ports: Vec<Port>,
     pub clock: Option<String>,
     pub reset: Option<String>,
+    // several clocks or resets, the first ones being the default domain
+    #[serde(default)]
+    pub clocks: Vec<Clock>,
+    #[serde(default)]
+    pub resets: Vec<Reset>,
+    // default polarity of resets, active low when unset
+    pub reset_polarity: Option<ResetPolarity>,
     #[serde(default = "default_use_clock_block")]
     pub use_clock_block: bool,
+    #[serde(default)]
+    pub clocking: Clocking,
+    pub protocol: Option<ProtocolKind>,
+    #[serde(default)]
+    pub roles: HashMap<String, Role>,
 
     #[serde(default)]
     pub item: Item,
+    #[serde(default)]
+    pub types: Vec<Type>,
+    #[serde(default)]
+    pub coverage: Coverage,
+    #[serde(default)]
+    pub sequences: Vec<Sequence>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(untagged)]
+pub enum Port {
+    Description(String),
+    Table(PortTable),
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Parameter {
+    pub name: String,
+    pub default: u32,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Clock {
+    pub name: String,
+    // harness clock period, in timeunits
+    pub period: Option<u32>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Reset {
+    pub name: String,
+    // clock domain, defaults to the first clock
+    pub clock: Option<String>,
+    pub polarity: Option<ResetPolarity>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ResetPolarity {
+    Low,
+    High,
+}
+
+// defaults for the three clocking blocks, overridden per block
+#[derive(Deserialize, Serialize, Debug, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Clocking {
+    pub edge: Option<ClockEdge>,
+    pub input_skew: Option<String>,
+    pub output_skew: Option<String>,
+    #[serde(default)]
+    pub cont_drv: ClockingBlock,
+    #[serde(default)]
+    pub resp_drv: ClockingBlock,
+    #[serde(default)]
+    pub mon: ClockingBlock,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ClockingBlock {
+    pub edge: Option<ClockEdge>,
+    pub input_skew: Option<String>,
+    pub output_skew: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ClockEdge {
+    Posedge,
+    Negedge,
+    Both,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProtocolKind {
+    ValidReady,
+    Apb,
+    Axi4Lite,
+    Axi4Stream,
+    Uart,
+    Spi,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(untagged)]
+pub enum Role {
+    Port(String),
+    Ports(Vec<String>),
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct PortTable {
+    pub name: String,
+    pub direction: Option<String>,
+    #[serde(default)]
+    pub dimensions: Vec<(Bound, Bound)>,
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+    // clock domain, defaults to the first clock
+    pub clock: Option<String>,
+    // item member carried by the port, and the 1 bit port qualifying it
+    pub member: Option<String>,
+    pub qualifier: Option<String>,
+}
+
+// dimension bound, either a value or an expression of the VIP parameters
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(untagged)]
+pub enum Bound {
+    Value(u32),
+    Expression(String),
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Type {
+    pub name: String,
+    pub kind: TypeKind,
+    #[serde(default)]
+    pub fields: Vec<String>,
+    #[serde(rename = "type")]
+    pub alias: Option<String>,
+    #[serde(default = "default_packed")]
+    pub packed: bool,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TypeKind {
+    Struct,
+    Union,
+    Alias,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Coverage {
+    pub goal: Option<u32>,
+    #[serde(default = "default_auto")]
+    pub auto: bool,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    #[serde(default)]
+    pub coverpoints: Vec<Coverpoint>,
+    #[serde(default)]
+    pub crosses: Vec<Cross>,
+}
+
+impl Default for Coverage {
+    fn default() -> Self {
+        Coverage {
+            goal: None,
+            auto: default_auto(),
+            exclude: Vec::new(),
+            coverpoints: Vec::new(),
+            crosses: Vec::new(),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Coverpoint {
+    pub name: Option<String>,
+    pub member: String,
+    pub goal: Option<u32>,
+    #[serde(default)]
+    pub bins: Vec<String>,
+    #[serde(default)]
+    pub ignore_bins: Vec<String>,
+    #[serde(default)]
+    pub illegal_bins: Vec<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Cross {
+    pub name: String,
+    pub coverpoints: Vec<String>,
+    pub goal: Option<u32>,
+    #[serde(default)]
+    pub ignore_bins: Vec<String>,
+    #[serde(default)]
+    pub illegal_bins: Vec<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Sequence {
+    pub name: String,
+    #[serde(default = "default_count")]
+    pub count: u32,
+    #[serde(default)]
+    pub constraints: Vec<String>,
+    // (min, max) clock cycles between items
+    pub delay: Option<(u32, u32)>,
+    #[serde(default)]
+    pub sequences: Vec<String>,
 }
 
 fn default_use_clock_block() -> bool {
     true
 }
 
+fn default_packed() -> bool {
+    true
+}
+
+fn default_policy() -> bool {
+    true
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_auto() -> bool {
+    true
+}
+
+fn default_count() -> u32 {
+    1
+}
+
 #[derive(Deserialize, Serialize, Debug, Default)]
 #[serde(deny_unknown_fields)]
 pub struct Item {
-    pub members: Vec<String>,
-    pub constraints: Vec<String>,
+    #[serde(default)]
+    pub members: Vec<Member>,
+    #[serde(default)]
+    pub constraints: Vec<Constraint>,
+    #[serde(default)]
+    pub enums: Vec<Enum>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(untagged)]
+pub enum Member {
+    Declaration(String),
+    Table(MemberTable),
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct MemberTable {
+    pub decl: String,
+    #[serde(default = "default_policy")]
+    pub compare: bool,
+    #[serde(default = "default_policy")]
+    pub pack: bool,
+    #[serde(default = "default_policy")]
+    pub print: bool,
+    #[serde(default = "default_policy")]
+    pub record: bool,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(untagged)]
+pub enum Constraint {
+    Expression(String),
+    Table(ConstraintTable),
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct ConstraintTable {
+    pub name: Option<String>,
+    pub body: String,
+    #[serde(default)]
+    pub soft: bool,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Enum {
+    pub name: String,
+    pub values: Vec<String>,
+    pub base: Option<String>,
 }
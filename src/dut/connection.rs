@@ -0,0 +1,516 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::dut::utils::{PortDirection, DUT};
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub enum ConnectionTerm {
+    Port {
+        name: String,
+        range: Option<(u32, u32)>,
+    },
+    Constant {
+        value: String,
+        width: Option<u32>,
+    },
+}
+
+impl std::fmt::Display for ConnectionTerm {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ConnectionTerm::Port { name, range } => match range {
+                Some((end, start)) if end == start => write!(f, "{}[{}]", name, end),
+                Some((end, start)) => write!(f, "{}[{}:{}]", name, end, start),
+                None => write!(f, "{}", name),
+            },
+            ConnectionTerm::Constant { value, width: _ } => write!(f, "{}", value),
+        }
+    }
+}
+
+impl ConnectionTerm {
+    // bits [high:low] (offsets from the lsb) of a constant assigned to `width` bits,
+    // unsized fill constants fitting any slice
+    pub fn get_constant_slice(&self, width: u32, low: u32, high: u32) -> Option<ConnectionTerm> {
+        let value = match self {
+            ConnectionTerm::Constant { value, width: _ } => value,
+            ConnectionTerm::Port { name: _, range: _ } => return None,
+        };
+        if value.starts_with('\'') {
+            return Some(self.clone());
+        }
+
+        let bits = get_constant_bits(value, width)?;
+        let slice: String = bits
+            .get(low as usize..=high as usize)?
+            .iter()
+            .rev()
+            .collect();
+        Some(ConnectionTerm::Constant {
+            value: format!("{}'b{}", high - low + 1, slice),
+            width: Some(high - low + 1),
+        })
+    }
+}
+
+// bits of a sized or decimal constant, lsb first, extended or truncated to `width`
+fn get_constant_bits(value: &str, width: u32) -> Option<Vec<char>> {
+    let (size, base, digits) = match value.split_once('\'') {
+        Some((size, value)) => {
+            let mut chars = value.chars();
+            let base = chars.next()?.to_ascii_lowercase();
+            (size.parse::<u32>().ok()?, base, chars.as_str())
+        }
+        None => (width, 'd', value),
+    };
+    let digits: String = digits
+        .chars()
+        .filter(|c| *c != '_')
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+
+    let mut bits: Vec<char> = Vec::new();
+    let (radix, digit_width) = match base {
+        'b' => (2, 1),
+        'o' => (8, 3),
+        'h' => (16, 4),
+        'd' => {
+            let value = digits.parse::<u128>().ok()?;
+            bits.extend(format!("{:b}", value).chars());
+            (10, 0)
+        }
+        _ => return None,
+    };
+    if radix != 10 {
+        for c in digits.chars() {
+            match c {
+                'x' | 'z' => bits.extend(std::iter::repeat_n(c, digit_width)),
+                _ => {
+                    let digit = c.to_digit(radix)?;
+                    bits.extend(format!("{:0w$b}", digit, w = digit_width).chars());
+                }
+            }
+        }
+    }
+
+    bits.reverse();
+    // extended to the constant size with a leading x or z, 0 otherwise
+    let fill = match bits.last() {
+        Some(c) if *c == 'x' || *c == 'z' => *c,
+        _ => '0',
+    };
+    bits.resize(size as usize, fill);
+    bits.resize(width as usize, '0');
+    Some(bits)
+}
+
+#[derive(Debug, Error)]
+pub enum ParseConnectionError {
+    #[error("invalid connection description (expected: '<port>', '<port>[<u32>:<u32>]', '{{<term>, <term>...}}' or '<constant>', found: {0})")]
+    InvalidConnectionDescription(String),
+
+    #[error("invalid port description (expected: '<port_name>', '<port_name>[<u32>]' or '<port_name>[<u32>:<u32>]', found: {0})")]
+    InvalidPortDescription(String),
+
+    #[error("invalid constant description (expected: '<u32>'<base><value>', '<u32>' or unsized ''0'/''1', found: {0})")]
+    InvalidConstantDescription(String),
+
+    #[error("unsized constant not allowed in concatenation (found: {0})")]
+    UnsizedConstantInConcatenation(String),
+
+    #[error("range is not a positive numeric value")]
+    InvalidRangeParsing(#[from] std::num::ParseIntError),
+}
+
+#[derive(Debug, Error)]
+pub enum ResolveConnectionError {
+    #[error("{0} does not exist")]
+    UnknownPort(String),
+
+    #[error("{0} is out of the declared bounds {1}")]
+    OutOfBounds(String, String),
+
+    #[error("{0} slices a multi-dimensional port (only whole connection supported)")]
+    MultiDimensionalSlice(String),
+}
+
+/// DUT side of a connection, terms are listed MSB first as in a SystemVerilog concatenation
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct ConnectionExpr {
+    pub terms: Vec<ConnectionTerm>,
+}
+
+/// Connection term checked against the DUT, `bits` are the (low, high) DUT port bits it covers
+#[derive(Clone, Debug)]
+pub struct ResolvedTerm {
+    pub term: ConnectionTerm,
+    pub width: Option<u32>,
+    pub bits: Option<(u32, u32)>,
+    pub direction: Option<PortDirection>,
+}
+
+impl ConnectionExpr {
+    pub fn is_whole_port(&self) -> bool {
+        self.get_whole_port().is_some()
+    }
+
+    // name of the port connected as a whole, without range nor concatenation
+    pub fn get_whole_port(&self) -> Option<&str> {
+        match &self.terms[..] {
+            [ConnectionTerm::Port { name, range: None }] => Some(name),
+            _ => None,
+        }
+    }
+
+    pub fn resolve(&self, dut: &DUT) -> Result<Vec<ResolvedTerm>, ResolveConnectionError> {
+        let mut resolved = Vec::new();
+        for t in &self.terms {
+            let resolved_term = match t {
+                ConnectionTerm::Port { name, range } => {
+                    let properties = match dut.ports.get(name) {
+                        Some(properties) => properties,
+                        None => return Err(ResolveConnectionError::UnknownPort(name.clone())),
+                    };
+                    let bits = match (range, properties.dimensions.len()) {
+                        (None, 1) => {
+                            let (end, start) = properties.dimensions[0];
+                            (end.min(start), end.max(start))
+                        }
+                        (None, _) => (0, properties.get_width() - 1),
+                        (Some((end, start)), 0) => {
+                            if *end != 0 || *start != 0 {
+                                return Err(ResolveConnectionError::OutOfBounds(
+                                    t.to_string(),
+                                    "[0:0]".to_string(),
+                                ));
+                            }
+                            (0, 0)
+                        }
+                        (Some((end, start)), 1) => {
+                            let (dim_end, dim_start) = properties.dimensions[0];
+                            let (low, high) = (dim_end.min(dim_start), dim_end.max(dim_start));
+                            let bits = (*end.min(start), *end.max(start));
+                            if bits.0 < low || bits.1 > high {
+                                return Err(ResolveConnectionError::OutOfBounds(
+                                    t.to_string(),
+                                    properties.get_dimensions_str(),
+                                ));
+                            }
+                            bits
+                        }
+                        (Some(_), _) => {
+                            return Err(ResolveConnectionError::MultiDimensionalSlice(
+                                t.to_string(),
+                            ))
+                        }
+                    };
+                    ResolvedTerm {
+                        term: t.clone(),
+                        width: Some(bits.1 - bits.0 + 1),
+                        bits: Some(bits),
+                        direction: Some(properties.direction.clone()),
+                    }
+                }
+                ConnectionTerm::Constant { value: _, width } => ResolvedTerm {
+                    term: t.clone(),
+                    width: *width,
+                    bits: None,
+                    direction: None,
+                },
+            };
+            resolved.push(resolved_term);
+        }
+        Ok(resolved)
+    }
+}
+
+impl std::fmt::Display for ConnectionExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.terms.len() == 1 {
+            write!(f, "{}", self.terms[0])
+        } else {
+            let terms: Vec<String> = self.terms.iter().map(|t| t.to_string()).collect();
+            write!(f, "{{{}}}", terms.join(", "))
+        }
+    }
+}
+
+impl FromStr for ConnectionExpr {
+    type Err = ParseConnectionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+
+        if let Some(inner) = s.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+            let mut terms = Vec::new();
+            for t in inner.split(',') {
+                let term: ConnectionTerm = t.parse()?;
+                if let ConnectionTerm::Constant { value, width: None } = term {
+                    return Err(Self::Err::UnsizedConstantInConcatenation(value));
+                }
+                terms.push(term);
+            }
+            Ok(ConnectionExpr { terms })
+        } else if s.is_empty() || s.contains(['{', '}', ',']) {
+            Err(Self::Err::InvalidConnectionDescription(s.to_string()))
+        } else {
+            let term = s.parse()?;
+            Ok(ConnectionExpr { terms: vec![term] })
+        }
+    }
+}
+
+impl FromStr for ConnectionTerm {
+    type Err = ParseConnectionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+
+        match s.chars().next() {
+            Some(c) if c.is_ascii_digit() || c == '\'' => parse_constant(s),
+            Some(c) if c.is_ascii_alphabetic() || c == '_' => parse_port(s),
+            _ => Err(Self::Err::InvalidConnectionDescription(s.to_string())),
+        }
+    }
+}
+
+pub fn parse_constant(s: &str) -> Result<ConnectionTerm, ParseConnectionError> {
+    let invalid = || ParseConnectionError::InvalidConstantDescription(s.to_string());
+
+    match s.split_once('\'') {
+        Some(("", value)) => {
+            if value == "0" || value == "1" {
+                Ok(ConnectionTerm::Constant {
+                    value: s.to_string(),
+                    width: None,
+                })
+            } else {
+                Err(invalid())
+            }
+        }
+        Some((width, value)) => {
+            let width = width.parse::<u32>().map_err(|_| invalid())?;
+            let mut digits = value.chars();
+            let base = digits.next().map(|c| c.to_ascii_lowercase());
+            let digits: String = digits.collect();
+            let is_valid_digit = |c: char| match base {
+                Some('b') => "01xz_".contains(c.to_ascii_lowercase()),
+                Some('o') => c.is_digit(8) || "xz_".contains(c.to_ascii_lowercase()),
+                Some('d') => c.is_ascii_digit() || c == '_',
+                Some('h') => c.is_ascii_hexdigit() || "xz_".contains(c.to_ascii_lowercase()),
+                _ => false,
+            };
+            if width == 0 || digits.is_empty() || !digits.chars().all(is_valid_digit) {
+                Err(invalid())
+            } else {
+                Ok(ConnectionTerm::Constant {
+                    value: s.to_string(),
+                    width: Some(width),
+                })
+            }
+        }
+        None => {
+            if s.chars().all(|c| c.is_ascii_digit()) {
+                Ok(ConnectionTerm::Constant {
+                    value: s.to_string(),
+                    width: None,
+                })
+            } else {
+                Err(invalid())
+            }
+        }
+    }
+}
+
+fn parse_port(s: &str) -> Result<ConnectionTerm, ParseConnectionError> {
+    let invalid = || ParseConnectionError::InvalidPortDescription(s.to_string());
+
+    let (name, range) = match s.split_once('[') {
+        Some((name, range)) => {
+            let range = range.strip_suffix(']').ok_or_else(invalid)?;
+            let range = match range.split_once(':') {
+                Some((end, start)) => (end.parse::<u32>()?, start.parse::<u32>()?),
+                None => {
+                    let bit = range.parse::<u32>()?;
+                    (bit, bit)
+                }
+            };
+            (name, Some(range))
+        }
+        None => (s, None),
+    };
+
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(invalid());
+    }
+
+    Ok(ConnectionTerm::Port {
+        name: name.to_string(),
+        range,
+    })
+}
+
+/// (low, high) bit ranges of each DUT port left unconnected by `connections`
+pub fn get_unconnected_ranges(
+    dut: &DUT,
+    connections: &[String],
+) -> BTreeMap<String, Vec<(u32, u32)>> {
+    let mut connected = BTreeMap::<String, Vec<(u32, u32)>>::new();
+    for c in connections {
+        let terms = match c.parse::<ConnectionExpr>().map(|expr| expr.resolve(dut)) {
+            Ok(Ok(terms)) => terms,
+            _ => continue,
+        };
+        for t in terms {
+            if let (ConnectionTerm::Port { name, range: _ }, Some(bits)) = (t.term, t.bits) {
+                connected.entry(name).or_default().push(bits);
+            }
+        }
+    }
+
+    let mut unconnected = BTreeMap::new();
+    for (name, properties) in &dut.ports {
+        let (low, high) = match properties.dimensions[..] {
+            [(end, start)] => (end.min(start), end.max(start)),
+            _ => (0, properties.get_width() - 1),
+        };
+
+        let mut bits = connected.remove(name).unwrap_or_default();
+        bits.sort();
+        let mut ranges = Vec::new();
+        let mut next = low;
+        for (connected_low, connected_high) in bits {
+            if connected_low > next {
+                ranges.push((next, connected_low - 1));
+            }
+            next = next.max(connected_high + 1);
+        }
+        if next <= high {
+            ranges.push((next, high));
+        }
+
+        if !ranges.is_empty() {
+            unconnected.insert(name.clone(), ranges);
+        }
+    }
+    unconnected
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{get_unconnected_ranges, ConnectionExpr, ConnectionTerm};
+    use crate::dut::utils::{PortProperties, DUT};
+
+    #[test]
+    fn successful_connections_parsing() {
+        let descriptions = vec![
+            "irq",
+            "irq[3:0]",
+            "irq[7:4]",
+            "irq[5]",
+            "  irq[0:3]  ",
+            "{irq[7:4], irq[1:0]}",
+            "{irq[3:0],4'b0}",
+            "{ 2'b01, data[5:0] }",
+            "1'b0",
+            "8'hFF",
+            "16'd42",
+            "'0",
+            "'1",
+            "0",
+        ];
+
+        for d in descriptions {
+            let parsed_connection = d.parse::<ConnectionExpr>();
+            assert!(parsed_connection.is_ok(), "{}", d);
+        }
+    }
+
+    #[test]
+    fn failed_connections_parsing() {
+        let descriptions = vec![
+            "",
+            "   ",
+            "irq[",
+            "irq[3:0",
+            "irq[a:0]",
+            "irq[-1:0]",
+            "irq [3:0]",
+            "{irq, '0}",
+            "{irq, {data}}",
+            "irq, data",
+            "8'hFG",
+            "2'b012",
+            "'2",
+            "0'b0",
+            "{}",
+        ];
+
+        for d in descriptions {
+            let parsed_connection = d.parse::<ConnectionExpr>();
+            assert!(parsed_connection.is_err(), "{}", d);
+        }
+    }
+
+    #[test]
+    fn unconnected_ranges() {
+        let mut ports = HashMap::new();
+        for (name, dimensions) in [
+            ("clk", vec![]),
+            ("irq", vec![(7, 0)]),
+            ("addr", vec![(31, 0)]),
+            ("data", vec![(15, 0)]),
+        ] {
+            let properties = PortProperties {
+                dimensions,
+                ..Default::default()
+            };
+            ports.insert(name.to_string(), properties);
+        }
+        let dut = DUT {
+            name: "dut".to_string(),
+            ports,
+        };
+        let connections = vec![
+            "clk".to_string(),
+            "irq[3:0]".to_string(),
+            "irq[6]".to_string(),
+            "{addr[31:2], 2'b00}".to_string(),
+        ];
+
+        let unconnected = get_unconnected_ranges(&dut, &connections);
+        assert_eq!(unconnected.get("clk"), None);
+        assert_eq!(unconnected["irq"], vec![(4, 5), (7, 7)]);
+        assert_eq!(unconnected["addr"], vec![(0, 1)]);
+        assert_eq!(unconnected["data"], vec![(0, 15)]);
+    }
+
+    #[test]
+    fn constant_slices() {
+        let slices = vec![
+            ("8'hFF", 8, (4, 7), "4'b1111"),
+            ("8'hA5", 8, (0, 3), "4'b0101"),
+            ("8'hA5", 8, (4, 7), "4'b1010"),
+            ("4'bx", 8, (2, 5), "4'b00xx"),
+            ("16'd300", 16, (8, 9), "2'b01"),
+            ("10", 8, (1, 3), "3'b101"),
+            ("3'o7", 4, (3, 3), "1'b0"),
+            ("'1", 8, (4, 7), "'1"),
+            ("'0", 8, (0, 0), "'0"),
+        ];
+
+        for (value, width, (low, high), slice) in slices {
+            let term = value.parse::<ConnectionTerm>().unwrap();
+            let sliced = term.get_constant_slice(width, low, high).unwrap();
+            assert_eq!(sliced.to_string(), slice, "{}", value);
+        }
+
+        let port = "irq".parse::<ConnectionTerm>().unwrap();
+        assert_eq!(port.get_constant_slice(8, 0, 3), None);
+    }
+}
@@ -1,2 +1,3 @@
+pub mod connection;
 pub mod parser;
 pub mod utils;
@@ -1,50 +1,127 @@
-use log::{debug, info, trace};
+use log::{debug, error, info, trace};
 use std::collections::HashMap;
 use std::path::PathBuf;
 use sv_parser::{
     parse_sv, unwrap_node, AnsiPortDeclaration, Locate, ModuleDeclarationAnsi, PortDirection,
     RefNode, SyntaxTree,
 };
+use thiserror::Error;
 
 use crate::config::project::DUT as DUTcfg;
-use crate::dut::utils::{Port, PortDirection as PortDir, PortProperties, DUT};
+use crate::dut::utils::{
+    fmt_suggestion, get_closest_name, Port, PortDirection as PortDir, PortProperties, DUT,
+};
+
+#[derive(Debug, Error)]
+pub enum ParseDUTError {
+    #[error("dut {name} not found in {path} (available modules: {}){}", .available.join(", "), fmt_suggestion(.suggestion))]
+    DUTNotFound {
+        name: String,
+        path: String,
+        available: Vec<String>,
+        suggestion: Option<String>,
+    },
+
+    #[error("dut {name} in {path} has a non-ANSI port list, only ANSI-style module headers are supported")]
+    NonAnsiDUT { name: String, path: String },
+}
 
 pub fn parse_dut(cfg: &DUTcfg) -> DUT {
-    info!("parsing dut file {}", cfg.path);
+    let syntax_tree = parse_dut_file(&cfg.path);
+
+    let dut_name = cfg.name.clone().unwrap();
+    let dut = get_dut(&syntax_tree, &dut_name, &cfg.path).unwrap_or_else(|e| {
+        error!("{}", e);
+        panic!("bad dut name {}", dut_name);
+    });
+    trace!("dut parsed:\n{:#?}", dut);
+    dut
+}
+
+// modules with ANSI-style headers, and names of the non-ANSI ones which cannot be used as DUT
+pub fn list_duts(path: &str) -> (Vec<DUT>, Vec<String>) {
+    let syntax_tree = parse_dut_file(path);
+
+    let duts = get_duts(&syntax_tree);
+    trace!("duts parsed:\n{:#?}", duts);
+    (duts, get_non_ansi_names(&syntax_tree))
+}
+
+fn parse_dut_file(path: &str) -> SyntaxTree {
+    info!("parsing dut file {}", path);
     let defines = HashMap::new();
     let includes: Vec<PathBuf> = Vec::new();
 
     let (syntax_tree, _def) =
-        parse_sv(&cfg.path, &defines, &includes, false, false).expect("failed to parse DUT file");
+        parse_sv(path, &defines, &includes, false, false).expect("failed to parse DUT file");
 
-    let dut_name = cfg.name.clone().unwrap();
-    let dut = get_dut(&syntax_tree, dut_name).expect("DUT not found in file");
-    trace!("dut parsed:\n{:#?}", dut);
-    dut
+    syntax_tree
+}
+
+fn get_duts(syntax_tree: &SyntaxTree) -> Vec<DUT> {
+    let mut duts = Vec::new();
+    for n in syntax_tree {
+        if let RefNode::ModuleDeclarationAnsi(x) = n {
+            let name = get_dut_name(syntax_tree, n.clone());
+            debug!("found module {}", name);
+            let ports = get_ports(syntax_tree, x);
+
+            duts.push(DUT { name, ports });
+        }
+    }
+    duts
 }
 
-fn get_dut(syntax_tree: &SyntaxTree, dut_name: String) -> Option<DUT> {
+fn get_dut(syntax_tree: &SyntaxTree, dut_name: &str, path: &str) -> Result<DUT, ParseDUTError> {
     info!("looking for dut {}", dut_name);
+    let mut available = Vec::new();
     for n in syntax_tree {
-        match n {
-            RefNode::ModuleDeclarationAnsi(x) => {
-                let name = get_dut_name(syntax_tree, x);
-                if name == dut_name {
-                    debug!("found matching module {}", name);
-                    let ports = get_ports(syntax_tree, x);
-
-                    return Some(DUT { name, ports });
-                } else {
-                    debug!("found module {}, passing", name);
-                }
+        if let RefNode::ModuleDeclarationAnsi(x) = n {
+            let name = get_dut_name(syntax_tree, n.clone());
+            if name == dut_name {
+                debug!("found matching module {}", name);
+                let ports = get_ports(syntax_tree, x);
+
+                return Ok(DUT { name, ports });
+            } else {
+                debug!("found module {}, passing", name);
+                available.push(name);
             }
-            _ => (),
         }
     }
-    None
+
+    if get_non_ansi_names(syntax_tree)
+        .iter()
+        .any(|n| n == dut_name)
+    {
+        return Err(ParseDUTError::NonAnsiDUT {
+            name: dut_name.to_string(),
+            path: path.to_string(),
+        });
+    }
+
+    let suggestion = get_closest_name(dut_name, &available);
+    Err(ParseDUTError::DUTNotFound {
+        name: dut_name.to_string(),
+        path: path.to_string(),
+        available,
+        suggestion,
+    })
 }
 
-fn get_dut_name(syntax_tree: &SyntaxTree, module: &ModuleDeclarationAnsi) -> String {
+fn get_non_ansi_names(syntax_tree: &SyntaxTree) -> Vec<String> {
+    let mut names = Vec::new();
+    for n in syntax_tree {
+        if let RefNode::ModuleDeclarationNonansi(_) = n {
+            let name = get_dut_name(syntax_tree, n.clone());
+            debug!("found non-ANSI module {}", name);
+            names.push(name);
+        }
+    }
+    names
+}
+
+fn get_dut_name(syntax_tree: &SyntaxTree, module: RefNode) -> String {
     let id = unwrap_node!(module, ModuleIdentifier).unwrap();
     let port_locate = get_identifier(id);
     let name = syntax_tree.get_str(&port_locate).unwrap().to_string();
@@ -58,12 +135,9 @@ fn get_ports(
 ) -> HashMap<String, PortProperties> {
     let mut ports = HashMap::new();
     for n in module {
-        match n {
-            RefNode::AnsiPortDeclaration(x) => {
-                let port = get_port(syntax_tree, x);
-                ports.insert(port.name, port.properties);
-            }
-            _ => (),
+        if let RefNode::AnsiPortDeclaration(x) = n {
+            let port = get_port(syntax_tree, x);
+            ports.insert(port.name, port.properties);
         }
     }
     ports
@@ -80,8 +154,7 @@ fn get_port(syntax_tree: &SyntaxTree, port: &AnsiPortDeclaration) -> Port {
         dimensions,
     };
 
-    let port = Port { name, properties };
-    port
+    Port { name, properties }
 }
 
 fn get_port_name(syntax_tree: &SyntaxTree, module: &AnsiPortDeclaration) -> String {
@@ -95,15 +168,12 @@ fn get_port_name(syntax_tree: &SyntaxTree, module: &AnsiPortDeclaration) -> Stri
 fn get_dimensions(syntax_tree: &SyntaxTree, port: &AnsiPortDeclaration) -> Vec<(u32, u32)> {
     let mut dimensions = Vec::new();
     for n in port {
-        match n {
-            RefNode::ConstantRange(x) => {
-                let end = syntax_tree.get_str(&x.nodes.0.clone()).unwrap();
-                let end = end.parse().unwrap();
-                let start = syntax_tree.get_str(&x.nodes.2.clone()).unwrap();
-                let start = start.parse().unwrap();
-                dimensions.push((end, start));
-            }
-            _ => (),
+        if let RefNode::ConstantRange(x) = n {
+            let end = syntax_tree.get_str(&x.nodes.0.clone()).unwrap();
+            let end = end.parse().unwrap();
+            let start = syntax_tree.get_str(&x.nodes.2.clone()).unwrap();
+            let start = start.parse().unwrap();
+            dimensions.push((end, start));
         }
     }
     dimensions
@@ -111,8 +181,8 @@ fn get_dimensions(syntax_tree: &SyntaxTree, port: &AnsiPortDeclaration) -> Vec<(
 
 fn get_direction(port: &AnsiPortDeclaration) -> PortDir {
     for n in port {
-        match n {
-            RefNode::PortDirection(x) => match x {
+        if let RefNode::PortDirection(x) = n {
+            match x {
                 PortDirection::Input(_) => {
                     return PortDir::INPUT;
                 }
@@ -123,8 +193,7 @@ fn get_direction(port: &AnsiPortDeclaration) -> PortDir {
                     return PortDir::INOUT;
                 }
                 _ => (),
-            },
-            _ => (),
+            }
         }
     }
     PortDir::INOUT
@@ -133,12 +202,49 @@ fn get_direction(port: &AnsiPortDeclaration) -> PortDir {
 fn get_identifier(node: RefNode) -> Option<Locate> {
     // unwrap_node! can take multiple types
     match unwrap_node!(node, SimpleIdentifier, EscapedIdentifier) {
-        Some(RefNode::SimpleIdentifier(x)) => {
-            return Some(x.nodes.0);
-        }
-        Some(RefNode::EscapedIdentifier(x)) => {
-            return Some(x.nodes.0);
-        }
+        Some(RefNode::SimpleIdentifier(x)) => Some(x.nodes.0),
+        Some(RefNode::EscapedIdentifier(x)) => Some(x.nodes.0),
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{get_dut, list_duts, parse_dut_file, ParseDUTError};
+
+    #[test]
+    fn non_ansi_modules() {
+        let path = std::env::temp_dir().join("kitsuvm_non_ansi_modules.sv");
+        std::fs::write(
+            &path,
+            r#"
+            module fifo (input logic clk, output logic [7:0] dout);
+            endmodule
+
+            module legacy (clk, dout);
+              input clk;
+              output [7:0] dout;
+            endmodule
+            "#,
+        )
+        .unwrap();
+        let path = path.to_str().unwrap();
+
+        // non-ANSI modules are reported apart instead of being silently omitted
+        let (duts, non_ansi) = list_duts(path);
+        let names: Vec<&str> = duts.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, vec!["fifo"]);
+        assert_eq!(non_ansi, vec!["legacy"]);
+
+        let syntax_tree = parse_dut_file(path);
+        assert!(get_dut(&syntax_tree, "fifo", path).is_ok());
+        assert!(matches!(
+            get_dut(&syntax_tree, "legacy", path),
+            Err(ParseDUTError::NonAnsiDUT { .. })
+        ));
+        assert!(matches!(
+            get_dut(&syntax_tree, "legacyy", path),
+            Err(ParseDUTError::DUTNotFound { .. })
+        ));
+    }
+}
@@ -11,6 +11,19 @@ pub struct DUT {
     pub ports: HashMap<String, PortProperties>,
 }
 
+impl std::fmt::Display for DUT {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} ({} ports)", self.name, self.ports.len())?;
+
+        let mut names: Vec<&String> = self.ports.keys().collect();
+        names.sort();
+        for n in names {
+            write!(f, "\n  {} {}", self.ports[n], n)?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Deserialize, Serialize, Clone, Default, Debug, PartialEq)]
 pub enum PortDirection {
     INPUT,
@@ -19,6 +32,29 @@ pub enum PortDirection {
     INOUT,
 }
 
+impl std::fmt::Display for PortDirection {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PortDirection::INPUT => f.pad("input"),
+            PortDirection::OUTPUT => f.pad("output"),
+            PortDirection::INOUT => f.pad("inout"),
+        }
+    }
+}
+
+impl FromStr for PortDirection {
+    type Err = ParsePortError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "in" | "input" => Ok(PortDirection::INPUT),
+            "out" | "output" => Ok(PortDirection::OUTPUT),
+            "inout" => Ok(PortDirection::INOUT),
+            _ => Err(Self::Err::InvalidDirectionDescription(s.to_string())),
+        }
+    }
+}
+
 impl Not for PortDirection {
     type Output = Self;
 
@@ -37,12 +73,64 @@ pub struct PortProperties {
     pub dimensions: Vec<(u32, u32)>,
 }
 
+impl PortProperties {
+    pub fn get_width(&self) -> u32 {
+        self.dimensions
+            .iter()
+            .map(|(end, start)| end.abs_diff(*start) + 1)
+            .product()
+    }
+
+    // same number of dimensions with the same widths and ranges order, a 1 bit port
+    // without dimensions matching [0:0]
+    pub fn has_same_dimensions(&self, other: &PortProperties) -> bool {
+        let shape = |p: &PortProperties| -> Vec<(u32, bool)> {
+            if p.dimensions.is_empty() {
+                return vec![(1, true)];
+            }
+            p.dimensions
+                .iter()
+                .map(|(end, start)| (end.abs_diff(*start) + 1, end >= start))
+                .collect()
+        };
+        shape(self) == shape(other)
+    }
+
+    pub fn get_dimensions_str(&self) -> String {
+        self.dimensions
+            .iter()
+            .map(|(end, start)| format!("[{}:{}]", end, start))
+            .collect::<Vec<String>>()
+            .join("")
+    }
+}
+
+impl std::fmt::Display for PortProperties {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.dimensions.is_empty() {
+            write!(f, "{:<6}", self.direction)
+        } else {
+            write!(f, "{:<6} {}", self.direction, self.get_dimensions_str())
+        }
+    }
+}
+
 #[derive(Deserialize, Serialize, Clone, Default, Debug)]
 pub struct Port {
     pub name: String,
     pub properties: PortProperties,
 }
 
+impl std::fmt::Display for Port {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.properties.dimensions.is_empty() {
+            write!(f, "{}", self.name)
+        } else {
+            write!(f, "{} {}", self.name, self.properties.get_dimensions_str())
+        }
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum ParsePortError {
     #[error("invalid port description (expected: '<port_name> <dim0> <dim1>...', found: {0})")]
@@ -51,6 +139,9 @@ pub enum ParsePortError {
     #[error("invalid port name description")]
     InvalidPortNameDescription,
 
+    #[error("invalid direction description (expected: 'in', 'out' or 'inout', found: {0})")]
+    InvalidDirectionDescription(String),
+
     #[error("invalid dimension description (expected: '[<u32>:<u32>]', found: {0})")]
     InvalidDimDescription(String),
 
@@ -64,8 +155,8 @@ impl FromStr for Port {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let split = s.split_whitespace().collect::<Vec<&str>>();
 
-        if split.len() > 0 {
-            let name = if split[0] == "" {
+        if !split.is_empty() {
+            let name = if split[0].is_empty() {
                 return Err(Self::Err::InvalidPortNameDescription);
             } else {
                 split[0].to_string()
@@ -73,8 +164,7 @@ impl FromStr for Port {
             let direction = PortDirection::INOUT;
             let mut dimensions = Vec::new();
             if split.len() > 1 {
-                for i in 1..split.len() {
-                    let s = split[i];
+                for s in split.iter().skip(1) {
                     let parsed_dim = s
                         .strip_prefix('[')
                         .and_then(|s| s.strip_suffix(']'))
@@ -107,9 +197,45 @@ impl FromStr for Port {
     }
 }
 
+pub fn get_closest_name(name: &str, candidates: &[String]) -> Option<String> {
+    let max_distance = std::cmp::max(name.len() / 3, 2);
+
+    candidates
+        .iter()
+        .map(|c| (get_edit_distance(name, c), c))
+        .filter(|(d, _c)| *d <= max_distance)
+        .min_by_key(|(d, _c)| *d)
+        .map(|(_d, c)| c.clone())
+}
+
+pub fn fmt_suggestion(suggestion: &Option<String>) -> String {
+    match suggestion {
+        Some(s) => format!(", did you mean {}?", s),
+        None => "".to_string(),
+    }
+}
+
+fn get_edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            let insertion = current[j] + 1;
+            let deletion = previous[j + 1] + 1;
+            current.push(substitution.min(insertion).min(deletion));
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
 #[cfg(test)]
 mod tests {
-    use super::Port;
+    use super::{get_closest_name, Port};
 
     #[test]
     fn successful_ports_parsing() {
@@ -156,4 +282,68 @@ mod tests {
             assert!(parsed_port.is_err());
         }
     }
+
+    #[test]
+    fn closest_names() {
+        let candidates = vec![
+            "fifo".to_string(),
+            "fifo_ctrl".to_string(),
+            "arbiter".to_string(),
+        ];
+
+        assert_eq!(
+            get_closest_name("fifi", &candidates),
+            Some("fifo".to_string())
+        );
+        assert_eq!(
+            get_closest_name("fifo_ctl", &candidates),
+            Some("fifo_ctrl".to_string())
+        );
+        assert_eq!(
+            get_closest_name("arbitre", &candidates),
+            Some("arbiter".to_string())
+        );
+        assert_eq!(get_closest_name("uart", &candidates), None);
+    }
+
+    #[test]
+    fn ports_width() {
+        let widths = vec![
+            ("port_name", 1),
+            ("data [31:0]", 32),
+            ("data [16:1]", 16),
+            ("data [0:7]", 8),
+            ("data [3:0] [7:0]", 32),
+        ];
+
+        for (d, width) in widths {
+            let parsed_port = d.parse::<Port>().unwrap();
+            assert_eq!(parsed_port.properties.get_width(), width);
+        }
+    }
+
+    #[test]
+    fn ports_dimensions() {
+        let dimensions = vec![
+            ("a [31:0]", "b [31:0]", true),
+            ("a [32:1]", "b [31:0]", true),
+            ("a", "b [0:0]", true),
+            ("a [3:0] [7:0]", "b [3:0] [7:0]", true),
+            ("a [3:0] [7:0]", "b [31:0]", false),
+            ("a [7:0] [3:0]", "b [3:0] [7:0]", false),
+            ("a [0:31]", "b [31:0]", false),
+        ];
+
+        for (a, b, same) in dimensions {
+            let a = a.parse::<Port>().unwrap();
+            let b = b.parse::<Port>().unwrap();
+            assert_eq!(
+                a.properties.has_same_dimensions(&b.properties),
+                same,
+                "{} {}",
+                a,
+                b
+            );
+        }
+    }
 }
@@ -6,14 +6,15 @@ use clap::Parser;
 use log::{debug, info, trace};
 use tera::Tera;
 
-use kitsuvm::cli::Args;
+use kitsuvm::cli::{Args, Command, DutCommand};
 use kitsuvm::config::{
-    check_i_v_compat, check_i_v_d_compat, instance::get_self_test_instances, parse_config_files,
-    parse_project_file, parse_vip_files,
+    check_d_connected, check_i_v_compat, check_i_v_d_compat, instance::get_self_test_instances,
+    parse_config_files, parse_project_file, parse_vip_files, project::Timescale,
 };
-use kitsuvm::dut::parser::parse_dut;
+use kitsuvm::dut::parser::{list_duts, parse_dut};
 use kitsuvm::render::{
     get_tera_dir, render_self_test, render_top, render_vips,
+    timing::get_render_timescale,
     vip::{get_render_vips, set_vips_port_dir},
 };
 
@@ -25,6 +26,11 @@ fn main() {
     let cli = Args::parse();
     trace!("cli parsed:\n{:#?}", cli);
 
+    if let Some(command) = &cli.command {
+        run_command(command, &cli);
+        return;
+    }
+
     let tera_dir = get_tera_dir(&cli);
 
     backup_output_directory(&cli);
@@ -36,6 +42,26 @@ fn main() {
     gen_top_vips(&cli, &tera_dir);
 }
 
+fn run_command(command: &Command, cli: &Args) {
+    match command {
+        Command::Dut { command } => match command {
+            DutCommand::List { path } => {
+                let path = match path {
+                    Some(path) => path.clone(),
+                    None => parse_project_file(cli.project.clone()).dut.path,
+                };
+                let (duts, non_ansi) = list_duts(&path);
+                for dut in duts {
+                    println!("{}", dut);
+                }
+                for name in non_ansi {
+                    println!("{} (non-ANSI port list, not supported)", name);
+                }
+            }
+        },
+    }
+}
+
 fn backup_output_directory(cli: &Args) {
     let output_dir_path = Path::new(&cli.output);
 
@@ -68,31 +94,35 @@ fn gen_self_test(cli: &Args, tera_dir: &Tera) {
         let instances = get_self_test_instances(v);
 
         debug!("rendering self-test {}", v.name);
-        render_self_test(&tera_dir, &v, &instances, cli, &project);
+        render_self_test(tera_dir, v, &instances, cli, &project);
     }
 }
 
 fn gen_top_vips(cli: &Args, tera_dir: &Tera) {
     if !cli.no_top {
-        let (project, mut instances, vips) = parse_config_files(&cli);
+        let (project, mut instances, vips) = parse_config_files(cli);
+        let mut vips = get_render_vips(&vips);
         instances.estimate_ids();
-        check_i_v_compat(&instances, &vips);
+        instances.set_connections(&vips, cli.strict);
+        instances.set_specializations(&vips);
+        check_i_v_compat(&instances, &vips, cli.strict);
 
         let dut = parse_dut(&project.dut);
-        check_i_v_d_compat(&instances, &vips, &dut);
-
-        let mut vips = get_render_vips(&vips);
+        check_i_v_d_compat(&instances, &vips, &dut, cli.strict);
+        check_d_connected(&instances, &vips, &dut, &project.dut);
+        instances.set_harness_assigns(&vips, &dut);
 
         debug!("rendering top");
-        render_top(&tera_dir, &vips, &instances, &cli, &project);
+        render_top(tera_dir, &vips, &instances, cli, &project, &dut);
 
         copy_dut_files(cli, dut.name.clone(), project.dut.path);
 
         if !cli.no_vips {
-            set_vips_port_dir(&mut vips, &instances, &dut);
+            set_vips_port_dir(&mut vips, &instances, &dut, cli.strict);
 
             debug!("rendering vips");
-            render_vips(&tera_dir, &vips, cli);
+            let timescale = get_render_timescale(&project.timescale);
+            render_vips(tera_dir, &vips, &timescale, cli);
         }
     } else {
         if !cli.no_vips {
@@ -101,8 +131,16 @@ fn gen_top_vips(cli: &Args, tera_dir: &Tera) {
 
             let vips = get_render_vips(&vips);
 
+            // the project file is optional without top
+            let timescale = if Path::new(&cli.project).is_file() {
+                parse_project_file(cli.project.clone()).timescale
+            } else {
+                debug!("no project file {}, default timescale", cli.project);
+                Timescale::default()
+            };
+
             debug!("rendering vips");
-            render_vips(&tera_dir, &vips, cli);
+            render_vips(tera_dir, &vips, &get_render_timescale(&timescale), cli);
         }
     }
 }
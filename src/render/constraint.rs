@@ -0,0 +1,200 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::config::vip::Constraint as Constraintcfg;
+use crate::render::member::is_identifier;
+
+const STATEMENT_KEYWORDS: [&str; 6] = ["solve", "foreach", "if", "else", "unique", "disable"];
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct Constraint {
+    pub name: String,
+    pub lines: Vec<String>,
+    pub is_enabled: bool,
+}
+
+#[derive(Debug, Error)]
+pub enum ParseConstraintError {
+    #[error("invalid constraint name {0}")]
+    InvalidConstraintName(String),
+
+    #[error("constraint {0} is empty")]
+    EmptyConstraint(String),
+
+    #[error("constraint {0} declared several times")]
+    DuplicatedConstraint(String),
+
+    #[error("soft constraint {name} only takes expressions, found: {statement} (use soft inside the body instead)")]
+    UnexpectedSoftStatement { name: String, statement: String },
+}
+
+// 'index' starts at 1 and names unnamed constraints 'c<index>'
+pub fn get_constraint(
+    constraint: &Constraintcfg,
+    index: usize,
+) -> Result<Constraint, ParseConstraintError> {
+    let (name, body, is_soft, is_enabled) = match constraint {
+        Constraintcfg::Expression(e) => (None, e, false, true),
+        Constraintcfg::Table(t) => (t.name.clone(), &t.body, t.soft, t.enabled),
+    };
+    let name = name.unwrap_or(format!("c{}", index));
+    if !is_identifier(&name) {
+        return Err(ParseConstraintError::InvalidConstraintName(name));
+    }
+
+    let lines = if is_soft {
+        let mut lines = Vec::new();
+        for statement in split_statements(body) {
+            if starts_with_keyword(&statement) || statement.starts_with('{') {
+                return Err(ParseConstraintError::UnexpectedSoftStatement { name, statement });
+            }
+            lines.push(format!("soft {};", statement.trim_start_matches("soft ")));
+        }
+        lines
+    } else {
+        let mut lines: Vec<String> = body
+            .lines()
+            .map(|l| l.trim().to_string())
+            .filter(|l| !l.is_empty())
+            .collect();
+        if let Some(last) = lines.last_mut() {
+            let is_block_end = last == "}" || starts_with_keyword(last);
+            if !last.ends_with(';') && !is_block_end {
+                last.push(';');
+            }
+        }
+        lines
+    };
+
+    if lines.is_empty() {
+        return Err(ParseConstraintError::EmptyConstraint(name));
+    }
+
+    Ok(Constraint {
+        name,
+        lines,
+        is_enabled,
+    })
+}
+
+pub fn get_constraints(
+    constraints: &[Constraintcfg],
+) -> Result<Vec<Constraint>, ParseConstraintError> {
+    let mut render_constraints: Vec<Constraint> = Vec::new();
+    for (i, c) in constraints.iter().enumerate() {
+        let constraint = get_constraint(c, i + 1)?;
+        if render_constraints.iter().any(|c| c.name == constraint.name) {
+            return Err(ParseConstraintError::DuplicatedConstraint(constraint.name));
+        }
+        render_constraints.push(constraint);
+    }
+    Ok(render_constraints)
+}
+
+fn starts_with_keyword(statement: &str) -> bool {
+    let keyword = statement.split_whitespace().next().unwrap_or_default();
+    let keyword = keyword.split('(').next().unwrap_or_default();
+    STATEMENT_KEYWORDS.contains(&keyword)
+}
+
+// splits on ';' outside of braces, e.g. 'a inside {1, 2}; b < 3' gives two statements
+fn split_statements(body: &str) -> Vec<String> {
+    let mut statements = Vec::new();
+    let mut statement = String::new();
+    let mut depth = 0;
+    for c in body.chars() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            ';' if depth == 0 => {
+                statements.push(
+                    statement
+                        .split_whitespace()
+                        .collect::<Vec<&str>>()
+                        .join(" "),
+                );
+                statement.clear();
+                continue;
+            }
+            _ => (),
+        }
+        statement.push(c);
+    }
+    statements.push(
+        statement
+            .split_whitespace()
+            .collect::<Vec<&str>>()
+            .join(" "),
+    );
+    statements.retain(|s| !s.is_empty());
+    statements
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_constraint, get_constraints};
+    use crate::config::vip::{Constraint as Constraintcfg, ConstraintTable};
+
+    fn table(name: Option<&str>, body: &str, soft: bool) -> Constraintcfg {
+        Constraintcfg::Table(ConstraintTable {
+            name: name.map(|n| n.to_string()),
+            body: body.to_string(),
+            soft,
+            enabled: true,
+        })
+    }
+
+    #[test]
+    fn successful_constraints_parsing() {
+        let c = get_constraint(
+            &Constraintcfg::Expression("rate inside {[1:9]}".to_string()),
+            2,
+        )
+        .unwrap();
+        assert_eq!(c.name, "c2");
+        assert_eq!(c.lines, vec!["rate inside {[1:9]};"]);
+
+        let c = get_constraint(
+            &table(
+                Some("len_c"),
+                "\n  solve mode before len;\n  mode == BURST -> len inside {[4:16]}\n",
+                false,
+            ),
+            1,
+        )
+        .unwrap();
+        assert_eq!(c.name, "len_c");
+        assert_eq!(
+            c.lines,
+            vec![
+                "solve mode before len;",
+                "mode == BURST -> len inside {[4:16]};"
+            ]
+        );
+
+        let c = get_constraint(&table(None, "if (a) { b < 2; }", false), 1).unwrap();
+        assert_eq!(c.lines, vec!["if (a) { b < 2; }"]);
+
+        let c = get_constraint(&table(None, "len inside {1, 2};\nrate < 4", true), 1).unwrap();
+        assert_eq!(c.lines, vec!["soft len inside {1, 2};", "soft rate < 4;"]);
+    }
+
+    #[test]
+    fn failed_constraints_parsing() {
+        let constraints = vec![
+            table(Some("2c"), "rate < 4", false),
+            table(None, "  \n ", false),
+            table(None, "solve a before b", true),
+            table(None, "foreach (q[i]) q[i] < 4", true),
+        ];
+        for c in constraints {
+            assert!(get_constraint(&c, 1).is_err(), "{:?}", c);
+        }
+
+        let duplicated = vec![
+            table(Some("c2"), "rate < 4", false),
+            Constraintcfg::Expression("rate > 1".to_string()),
+        ];
+        assert!(get_constraints(&duplicated).is_err());
+    }
+}
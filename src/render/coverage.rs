@@ -0,0 +1,440 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::config::vip::{Coverage as Coveragecfg, Coverpoint as Coverpointcfg, Cross as Crosscfg};
+use crate::dut::utils::{fmt_suggestion, get_closest_name};
+use crate::render::member::{is_identifier, EnumType, Member, TypeCategory};
+
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq)]
+pub struct Coverage {
+    pub goal: Option<u32>,
+    pub coverpoints: Vec<Coverpoint>,
+    pub crosses: Vec<Cross>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct Coverpoint {
+    pub name: String,
+    // member path sampled from the item, e.g. 'hdr.len'
+    pub member: String,
+    pub goal: Option<u32>,
+    pub bins: Vec<Bin>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct Cross {
+    pub name: String,
+    pub coverpoints: Vec<String>,
+    pub goal: Option<u32>,
+    pub bins: Vec<Bin>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct Bin {
+    // 'bins', 'ignore_bins' or 'illegal_bins'
+    pub kind: String,
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Debug, Error)]
+pub enum ParseCoverageError {
+    #[error("invalid bin description (expected: '<name> = <bins expression>' or '<name>[] = <bins expression>', found: {0})")]
+    InvalidBinDescription(String),
+
+    #[error("invalid coverpoint or cross name {0}")]
+    InvalidName(String),
+
+    #[error("coverpoint or cross {0} declared several times")]
+    DuplicatedName(String),
+
+    #[error("unknown member {member} in {context}{}", fmt_suggestion(.suggestion))]
+    UnknownMember {
+        member: String,
+        context: String,
+        suggestion: Option<String>,
+    },
+
+    #[error("unknown coverpoint {coverpoint} in cross {cross}{}", fmt_suggestion(.suggestion))]
+    UnknownCoverpoint {
+        coverpoint: String,
+        cross: String,
+        suggestion: Option<String>,
+    },
+
+    #[error("cross {0} needs at least two coverpoints")]
+    NotEnoughCoverpoints(String),
+
+    #[error("goal {goal} of {name} is not a percentage")]
+    InvalidGoal { name: String, goal: u32 },
+}
+
+pub fn get_coverage(
+    coverage: &Coveragecfg,
+    members: &[Member],
+    enums: &[EnumType],
+) -> Result<Coverage, ParseCoverageError> {
+    let paths: Vec<String> = members
+        .iter()
+        .flat_map(|m| &m.fields)
+        .map(|f| f.path.clone())
+        .collect();
+    check_goal("covergroup", coverage.goal)?;
+    for e in &coverage.exclude {
+        check_member(e, "coverage exclude", &paths)?;
+    }
+
+    let mut coverpoints: Vec<Coverpoint> = Vec::new();
+    for c in &coverage.coverpoints {
+        let coverpoint = get_coverpoint(c, &paths)?;
+        if coverpoints.iter().any(|cp| cp.name == coverpoint.name) {
+            return Err(ParseCoverageError::DuplicatedName(coverpoint.name));
+        }
+        coverpoints.push(coverpoint);
+    }
+
+    if coverage.auto {
+        let explicit: Vec<String> = coverpoints.iter().map(|cp| cp.member.clone()).collect();
+        let auto_members = members
+            .iter()
+            .flat_map(|m| &m.fields)
+            .filter(|f| !coverage.exclude.iter().any(|e| is_path_in(&f.path, e)))
+            .filter(|f| !explicit.contains(&f.path));
+        for f in auto_members {
+            let bins = match (&f.category, &f.enum_kind) {
+                (TypeCategory::Enum, Some(enum_kind)) => {
+                    let values = enums
+                        .iter()
+                        .find(|e| &e.name == enum_kind)
+                        .map(|e| e.values.iter().map(|v| v.name.clone()).collect::<Vec<_>>())
+                        .unwrap_or_default();
+                    vec![Bin {
+                        kind: "bins".to_string(),
+                        name: "values[]".to_string(),
+                        value: format!("{{ {} }}", values.join(", ")),
+                    }]
+                }
+                // time stamps would only fill auto bins with unrelated values
+                (TypeCategory::Integral, _) if f.kind != "time" => Vec::new(),
+                _ => continue,
+            };
+            let name = get_default_name(&f.path);
+            if coverpoints.iter().any(|cp| cp.name == name) {
+                return Err(ParseCoverageError::DuplicatedName(name));
+            }
+            coverpoints.push(Coverpoint {
+                name,
+                member: f.path.clone(),
+                goal: None,
+                bins,
+            });
+        }
+    }
+
+    let coverpoints_names: Vec<String> = coverpoints.iter().map(|cp| cp.name.clone()).collect();
+    let mut crosses: Vec<Cross> = Vec::new();
+    for c in &coverage.crosses {
+        let cross = get_cross(c, &coverpoints_names)?;
+        if coverpoints_names.contains(&cross.name) || crosses.iter().any(|x| x.name == cross.name) {
+            return Err(ParseCoverageError::DuplicatedName(cross.name));
+        }
+        crosses.push(cross);
+    }
+
+    Ok(Coverage {
+        goal: coverage.goal,
+        coverpoints,
+        crosses,
+    })
+}
+
+fn get_coverpoint(
+    coverpoint: &Coverpointcfg,
+    paths: &[String],
+) -> Result<Coverpoint, ParseCoverageError> {
+    let name = coverpoint
+        .name
+        .clone()
+        .unwrap_or(get_default_name(&coverpoint.member));
+    if !is_identifier(&name) {
+        return Err(ParseCoverageError::InvalidName(name));
+    }
+    check_member(&coverpoint.member, &name, paths)?;
+    check_goal(&name, coverpoint.goal)?;
+
+    Ok(Coverpoint {
+        bins: get_bins(
+            &coverpoint.bins,
+            &coverpoint.ignore_bins,
+            &coverpoint.illegal_bins,
+        )?,
+        name,
+        member: coverpoint.member.clone(),
+        goal: coverpoint.goal,
+    })
+}
+
+fn get_cross(cross: &Crosscfg, coverpoints_names: &[String]) -> Result<Cross, ParseCoverageError> {
+    if !is_identifier(&cross.name) {
+        return Err(ParseCoverageError::InvalidName(cross.name.clone()));
+    }
+    if cross.coverpoints.len() < 2 {
+        return Err(ParseCoverageError::NotEnoughCoverpoints(cross.name.clone()));
+    }
+    for cp in &cross.coverpoints {
+        if !coverpoints_names.contains(cp) {
+            return Err(ParseCoverageError::UnknownCoverpoint {
+                coverpoint: cp.clone(),
+                cross: cross.name.clone(),
+                suggestion: get_closest_name(cp, coverpoints_names),
+            });
+        }
+    }
+    check_goal(&cross.name, cross.goal)?;
+
+    Ok(Cross {
+        name: cross.name.clone(),
+        coverpoints: cross.coverpoints.clone(),
+        goal: cross.goal,
+        bins: get_bins(&[], &cross.ignore_bins, &cross.illegal_bins)?,
+    })
+}
+
+fn get_bins(
+    bins: &[String],
+    ignore_bins: &[String],
+    illegal_bins: &[String],
+) -> Result<Vec<Bin>, ParseCoverageError> {
+    let mut render_bins = Vec::new();
+    for (kind, descriptions) in [
+        ("bins", bins),
+        ("ignore_bins", ignore_bins),
+        ("illegal_bins", illegal_bins),
+    ] {
+        for d in descriptions {
+            render_bins.push(get_bin(kind, d)?);
+        }
+    }
+    Ok(render_bins)
+}
+
+// expects '<name> = <value>' or '<name>[<size (opt)>] = <value>'
+fn get_bin(kind: &str, description: &str) -> Result<Bin, ParseCoverageError> {
+    let invalid = || ParseCoverageError::InvalidBinDescription(description.to_string());
+    // the name ends at the first '=', which must not be part of an '==' in the value
+    let name_end = description
+        .find(|c: char| !(c.is_alphanumeric() || c.is_whitespace() || "_$[]".contains(c)))
+        .ok_or_else(invalid)?;
+    let (name, value) = description.split_at(name_end);
+    let value = value
+        .strip_prefix('=')
+        .filter(|v| !v.starts_with('='))
+        .ok_or_else(invalid)?;
+    let (name, value) = (name.trim(), value.trim());
+
+    let identifier = match name.split_once('[') {
+        Some((identifier, size)) => {
+            let size = size.strip_suffix(']').ok_or_else(invalid)?;
+            if !size.is_empty() && size.parse::<u32>().is_err() {
+                return Err(invalid());
+            }
+            identifier.trim()
+        }
+        None => name,
+    };
+    if !is_identifier(identifier) || value.is_empty() {
+        return Err(invalid());
+    }
+
+    Ok(Bin {
+        kind: kind.to_string(),
+        name: name.to_string(),
+        value: value.to_string(),
+    })
+}
+
+// 'paths' holds the item fields paths, a member or struct path covers the fields below it
+fn check_member(member: &str, context: &str, paths: &[String]) -> Result<(), ParseCoverageError> {
+    if paths.iter().any(|p| is_path_in(p, member)) {
+        Ok(())
+    } else {
+        Err(ParseCoverageError::UnknownMember {
+            member: member.to_string(),
+            context: context.to_string(),
+            suggestion: get_closest_name(member, paths),
+        })
+    }
+}
+
+// true if 'path' is 'parent' or one of its fields, e.g. 'hdr.len' in 'hdr'
+fn is_path_in(path: &str, parent: &str) -> bool {
+    path.strip_prefix(parent)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+}
+
+fn check_goal(name: &str, goal: Option<u32>) -> Result<(), ParseCoverageError> {
+    match goal {
+        Some(goal) if goal > 100 => Err(ParseCoverageError::InvalidGoal {
+            name: name.to_string(),
+            goal,
+        }),
+        _ => Ok(()),
+    }
+}
+
+fn get_default_name(member: &str) -> String {
+    format!("cp_{}", member.replace('.', "_"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_coverage, ParseCoverageError};
+    use crate::config::vip::{
+        Coverage as Coveragecfg, Coverpoint as Coverpointcfg, Cross, TypeKind,
+    };
+    use crate::render::member::{Member, TypeScope, UserType};
+
+    fn coverpoint(member: &str, bins: Vec<&str>) -> Coverpointcfg {
+        Coverpointcfg {
+            name: None,
+            member: member.to_string(),
+            goal: None,
+            bins: bins.into_iter().map(|b| b.to_string()).collect(),
+            ignore_bins: Vec::new(),
+            illegal_bins: vec!["bad = {15}".to_string()],
+        }
+    }
+
+    fn cross(coverpoints: Vec<&str>) -> Cross {
+        Cross {
+            name: "len_x_rate".to_string(),
+            coverpoints: coverpoints.into_iter().map(|c| c.to_string()).collect(),
+            goal: Some(80),
+            ignore_bins: Vec::new(),
+            illegal_bins: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn coverage_model() {
+        let members: Vec<Member> = [
+            "rand bit [3:0] len",
+            "rand int rate",
+            "bit [31:0] data",
+            "time stamp",
+            "realtime at",
+        ]
+        .iter()
+        .map(|m| m.parse().unwrap())
+        .collect();
+
+        let cfg = Coveragecfg {
+            goal: Some(90),
+            auto: true,
+            exclude: vec!["data".to_string()],
+            coverpoints: vec![coverpoint(
+                "len",
+                vec!["low[] = {[0:3]}", "high = {[4:14]}"],
+            )],
+            crosses: vec![cross(vec!["cp_len", "cp_rate"])],
+        };
+        let coverage = get_coverage(&cfg, &members, &[]).unwrap();
+        let names: Vec<&str> = coverage
+            .coverpoints
+            .iter()
+            .map(|cp| cp.name.as_str())
+            .collect();
+        // time stamps get no automatic coverpoint
+        assert_eq!(names, vec!["cp_len", "cp_rate"]);
+        assert_eq!(coverage.coverpoints[0].bins.len(), 3);
+        assert_eq!(coverage.coverpoints[0].bins[2].kind, "illegal_bins");
+        assert_eq!(coverage.crosses.len(), 1);
+
+        let failing = vec![
+            Coveragecfg {
+                coverpoints: vec![coverpoint("lenn", vec![])],
+                ..Default::default()
+            },
+            Coveragecfg {
+                coverpoints: vec![coverpoint("len", vec!["low {[0:3]}"])],
+                ..Default::default()
+            },
+            Coveragecfg {
+                coverpoints: vec![coverpoint("len", vec!["low[a] = {[0:3]}"])],
+                ..Default::default()
+            },
+            Coveragecfg {
+                crosses: vec![cross(vec!["cp_len", "cp_data"])],
+                exclude: vec!["data".to_string()],
+                ..Default::default()
+            },
+            Coveragecfg {
+                crosses: vec![cross(vec!["cp_len"])],
+                ..Default::default()
+            },
+            Coveragecfg {
+                goal: Some(101),
+                ..Default::default()
+            },
+        ];
+        for cfg in failing {
+            assert!(get_coverage(&cfg, &members, &[]).is_err(), "{:?}", cfg);
+        }
+    }
+
+    #[test]
+    fn coverage_paths() {
+        let header = UserType {
+            name: "header_t".to_string(),
+            kind: TypeKind::Struct,
+            packed: true,
+            fields: ["bit [3:0] len", "bit kind"]
+                .iter()
+                .map(|m| m.parse().unwrap())
+                .collect(),
+            alias: None,
+        };
+        let scope = TypeScope {
+            enums: &[],
+            types: &[header],
+        };
+        let mut hdr: Member = "rand header_t hdr".parse().unwrap();
+        hdr.fields = scope.get_fields(&hdr);
+        let members = vec![hdr];
+
+        let cfg = Coveragecfg {
+            auto: true,
+            exclude: vec!["hdr.kind".to_string()],
+            coverpoints: vec![coverpoint(
+                "hdr.len",
+                vec!["odd[] = {[0:15]} with (item % 2 == 1)", "zero = {0}"],
+            )],
+            ..Default::default()
+        };
+        let coverage = get_coverage(&cfg, &members, &[]).unwrap();
+        assert_eq!(coverage.coverpoints.len(), 1);
+        let bins = &coverage.coverpoints[0].bins;
+        assert_eq!(bins[0].name, "odd[]");
+        assert_eq!(bins[0].value, "{[0:15]} with (item % 2 == 1)");
+
+        // the whole path is checked, not only the member
+        let cfg = Coveragecfg {
+            coverpoints: vec![coverpoint("hdr.lne", vec![])],
+            ..Default::default()
+        };
+        match get_coverage(&cfg, &members, &[]) {
+            Err(ParseCoverageError::UnknownMember { suggestion, .. }) => {
+                assert_eq!(suggestion, Some("hdr.len".to_string()))
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        for bin in ["low == {3}", "low <= 3", "= {3}", "low[] {3} = 1"] {
+            let cfg = Coveragecfg {
+                coverpoints: vec![coverpoint("hdr", vec![bin])],
+                ..Default::default()
+            };
+            assert!(get_coverage(&cfg, &members, &[]).is_err(), "{}", bin);
+        }
+    }
+}
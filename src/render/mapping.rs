@@ -0,0 +1,250 @@
+use log::warn;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::dut::utils::{fmt_suggestion, get_closest_name};
+use crate::render::member::{Member, TypeCategory, TypeScope};
+use crate::render::vip::VIPPort;
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct Mapping {
+    pub fields: Vec<MappedPort>,
+    // 1 bit ports qualifying the mapped ports, driven high for one cycle per item
+    pub qualifiers: Vec<String>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct MappedPort {
+    pub port: String,
+    // member path in the item, e.g. 'hdr.len'
+    pub member: String,
+    pub enum_kind: Option<String>,
+    // port sampled only when the qualifier is high, always otherwise
+    pub qualifier: Option<String>,
+}
+
+#[derive(Debug, Error)]
+pub enum ParseMappingError {
+    #[error("ports mapped to item members require a VIP clock and clocking blocks")]
+    MissingClock,
+
+    #[error("port {0} mapped to an item member while the VIP uses a protocol (use [roles])")]
+    WithProtocol(String),
+
+    #[error("unknown item member {member} for port {port}{}", fmt_suggestion(.suggestion))]
+    UnknownMember {
+        port: String,
+        member: String,
+        suggestion: Option<String>,
+    },
+
+    #[error("item member {member} mapped to port {port} must be of integral or enum type")]
+    InvalidMember { port: String, member: String },
+
+    #[error("port {0} has a qualifier but no item member")]
+    QualifierWithoutMember(String),
+
+    #[error("unknown qualifier {qualifier} for port {port}{}", fmt_suggestion(.suggestion))]
+    UnknownQualifier {
+        port: String,
+        qualifier: String,
+        suggestion: Option<String>,
+    },
+
+    #[error("qualifier {0} must be a 1 bit port not mapped to an item member")]
+    InvalidQualifier(String),
+}
+
+pub struct MappingScope<'a> {
+    pub ports: &'a [VIPPort],
+    pub members: &'a [Member],
+    pub types: &'a TypeScope<'a>,
+    pub has_clock: bool,
+    pub has_protocol: bool,
+}
+
+pub fn get_mapping(scope: MappingScope) -> Result<Option<Mapping>, ParseMappingError> {
+    let mapped: Vec<&VIPPort> = scope.ports.iter().filter(|p| p.member.is_some()).collect();
+    if let Some(p) = scope
+        .ports
+        .iter()
+        .find(|p| p.member.is_none() && p.qualifier.is_some())
+    {
+        return Err(ParseMappingError::QualifierWithoutMember(p.name.clone()));
+    }
+    if mapped.is_empty() {
+        return Ok(None);
+    }
+    if scope.has_protocol {
+        return Err(ParseMappingError::WithProtocol(mapped[0].name.clone()));
+    }
+    if !scope.has_clock {
+        return Err(ParseMappingError::MissingClock);
+    }
+
+    let ports_names: Vec<String> = scope.ports.iter().map(|p| p.name.clone()).collect();
+    let mut fields = Vec::new();
+    let mut qualifiers: Vec<String> = Vec::new();
+    for p in mapped {
+        let member = p.member.clone().unwrap();
+        let enum_kind = get_member_kind(&scope, &p.name, &member)?;
+
+        if let Some(qualifier) = &p.qualifier {
+            let q = match scope.ports.iter().find(|q| &q.name == qualifier) {
+                Some(q) => q,
+                None => {
+                    return Err(ParseMappingError::UnknownQualifier {
+                        port: p.name.clone(),
+                        qualifier: qualifier.clone(),
+                        suggestion: get_closest_name(qualifier, &ports_names),
+                    })
+                }
+            };
+            if q.member.is_some() || get_port_width(q) != 1 {
+                return Err(ParseMappingError::InvalidQualifier(qualifier.clone()));
+            }
+            if !qualifiers.contains(qualifier) {
+                qualifiers.push(qualifier.clone());
+            }
+        }
+
+        fields.push(MappedPort {
+            port: p.name.clone(),
+            member,
+            enum_kind,
+            qualifier: p.qualifier.clone(),
+        });
+    }
+
+    for p in scope
+        .ports
+        .iter()
+        .filter(|p| p.member.is_none() && !qualifiers.contains(&p.name))
+    {
+        warn!(
+            "port {} neither mapped to an item member nor a qualifier",
+            p.name
+        );
+    }
+
+    Ok(Some(Mapping { fields, qualifiers }))
+}
+
+// enum type of the member, which is either a top level member or a struct field path
+fn get_member_kind(
+    scope: &MappingScope,
+    port: &str,
+    path: &str,
+) -> Result<Option<String>, ParseMappingError> {
+    let invalid = || ParseMappingError::InvalidMember {
+        port: port.to_string(),
+        member: path.to_string(),
+    };
+
+    if let Some(member) = scope.members.iter().find(|m| m.name == path) {
+        let (category, enum_kind) = scope.types.get_category(member);
+        if !matches!(category, TypeCategory::Integral | TypeCategory::Enum) {
+            return Err(invalid());
+        }
+        let port_width = get_port_width(scope.ports.iter().find(|p| p.name == port).unwrap());
+        match scope.types.get_width(member) {
+            Some(width) if width != port_width => warn!(
+                "port {} ({} bits) and item member {} ({} bits) widths differ",
+                port, port_width, member.name, width
+            ),
+            _ => (),
+        }
+        return Ok(enum_kind);
+    }
+
+    match scope
+        .members
+        .iter()
+        .flat_map(|m| &m.fields)
+        .find(|f| f.path == path)
+    {
+        Some(f) if matches!(f.category, TypeCategory::Integral | TypeCategory::Enum) => {
+            Ok(f.enum_kind.clone())
+        }
+        Some(_) => Err(invalid()),
+        None => {
+            let members_names: Vec<String> = scope.members.iter().map(|m| m.name.clone()).collect();
+            let root = path.split('.').next().unwrap_or_default();
+            Err(ParseMappingError::UnknownMember {
+                port: port.to_string(),
+                member: path.to_string(),
+                suggestion: get_closest_name(root, &members_names),
+            })
+        }
+    }
+}
+
+fn get_port_width(port: &VIPPort) -> u32 {
+    port.properties
+        .dimensions
+        .iter()
+        .map(|(end, start)| end.abs_diff(*start) + 1)
+        .product()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_mapping, MappingScope};
+    use crate::render::member::{Member, TypeScope};
+    use crate::render::vip::VIPPort;
+
+    fn port(description: &str, member: Option<&str>, qualifier: Option<&str>) -> VIPPort {
+        VIPPort {
+            member: member.map(|m| m.to_string()),
+            qualifier: qualifier.map(|q| q.to_string()),
+            ..description.parse().unwrap()
+        }
+    }
+
+    #[test]
+    fn port_mapping() {
+        let members: Vec<Member> = ["rand bit [15:0] payload", "string tag"]
+            .iter()
+            .map(|m| m.parse().unwrap())
+            .collect();
+        let types = TypeScope {
+            enums: &[],
+            types: &[],
+        };
+        let get = |ports: &[VIPPort], has_protocol: bool| {
+            get_mapping(MappingScope {
+                ports,
+                members: &members,
+                types: &types,
+                has_clock: true,
+                has_protocol,
+            })
+        };
+
+        let ports = vec![
+            port("data out [15:0]", Some("payload"), Some("data_vld")),
+            port("data_vld out", None, None),
+        ];
+        let mapping = get(&ports, false).unwrap().unwrap();
+        assert_eq!(mapping.fields[0].member, "payload");
+        assert_eq!(mapping.qualifiers, vec!["data_vld"]);
+        assert!(get(&[port("data_vld out", None, None)], false)
+            .unwrap()
+            .is_none());
+        assert!(get(&ports, true).is_err());
+
+        let failing = vec![
+            vec![port("data [15:0]", Some("paylod"), None)],
+            vec![port("data [15:0]", Some("tag"), None)],
+            vec![port("data [15:0]", None, Some("data_vld"))],
+            vec![port("data [15:0]", Some("payload"), Some("data_vl"))],
+            vec![
+                port("data [15:0]", Some("payload"), Some("data_vld")),
+                port("data_vld [1:0]", None, None),
+            ],
+        ];
+        for ports in failing {
+            assert!(get(&ports, false).is_err(), "{:?}", ports);
+        }
+    }
+}
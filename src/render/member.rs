@@ -0,0 +1,1219 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::config::vip::{Enum as Enumcfg, Member as Membercfg, Type as Typecfg, TypeKind};
+
+const INTEGER_VECTOR_TYPES: [&str; 3] = ["bit", "logic", "reg"];
+const INTEGER_ATOM_TYPES: [&str; 6] = ["byte", "shortint", "int", "longint", "integer", "time"];
+const NON_INTEGER_TYPES: [&str; 3] = ["real", "shortreal", "realtime"];
+const OTHER_TYPES: [&str; 3] = ["string", "chandle", "event"];
+const KEYWORDS: [&str; 5] = ["rand", "randc", "signed", "unsigned", "const"];
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct Member {
+    pub name: String,
+    // data type, signing and packed dimensions, e.g. 'logic signed [7:0]'
+    pub kind: String,
+    pub base_kind: String,
+    pub signing: Option<String>,
+    pub packed_dimensions: Vec<String>,
+    pub unpacked_dimensions: Vec<UnpackedDimension>,
+    pub default: Option<String>,
+    pub is_randomized: bool,
+    pub is_cyclic: bool,
+    // leaves used for field-wise compare, print and pack, e.g. 'hdr.len'
+    pub fields: Vec<MemberField>,
+    pub is_compared: bool,
+    pub is_packed: bool,
+    pub is_printed: bool,
+    pub is_recorded: bool,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct MemberField {
+    pub path: String,
+    pub kind: String,
+    pub category: TypeCategory,
+    // enum type of the field (or of its elements for arrays)
+    pub enum_kind: Option<String>,
+    pub array: Option<ArrayField>,
+}
+
+// selects copy, compare, pack and print code in templates
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TypeCategory {
+    Integral,
+    Enum,
+    Real,
+    String,
+    // class handles, i.e. types not declared in the VIP
+    Object,
+    Array,
+    // chandle, event, unpacked unions and nested arrays, only copied
+    Opaque,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct ArrayField {
+    // kind of the outermost unpacked dimension
+    pub kind: ArrayKind,
+    // foreach loop indices, e.g. 'i0, i1'
+    pub indices: String,
+    // element access, e.g. 'data[i0][i1]'
+    pub element: String,
+    // element name format, e.g. 'data[%0d][%0d]'
+    pub element_name: String,
+    pub element_kind: String,
+    pub element_category: TypeCategory,
+    pub is_packable: bool,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ArrayKind {
+    Fixed,
+    Dynamic,
+    Queue,
+    Associative,
+}
+
+#[derive(Debug, Error)]
+pub enum ParseMemberError {
+    #[error("invalid member description (expected: <rand|randc (opt)> <type> <signing (opt)> <packed dims (opt)> <name> <unpacked dims (opt)> <= default (opt)>, found: {0})")]
+    InvalidMemberDescription(String),
+
+    #[error("unbalanced brackets in member {0}")]
+    UnbalancedBrackets(String),
+
+    #[error("missing default value after '=' in member {0}")]
+    MissingDefault(String),
+
+    #[error("invalid identifier {identifier} in member {member}")]
+    InvalidIdentifier { identifier: String, member: String },
+
+    #[error("{signing} not allowed on type {kind} in member {member}")]
+    UnexpectedSigning {
+        signing: String,
+        kind: String,
+        member: String,
+    },
+
+    #[error("packed dimension {dimension} not allowed on type {kind} in member {member} (expected: '[<msb>:<lsb>]' on bit, logic, reg or user types)")]
+    InvalidPackedDimension {
+        dimension: String,
+        kind: String,
+        member: String,
+    },
+
+    #[error("invalid unpacked dimension {dimension} in member {member} (expected: '[<size>]', '[<msb>:<lsb>]', '[]', '[$]', '[$:<max>]', '[*]' or '[<index type>]')")]
+    InvalidUnpackedDimension { dimension: String, member: String },
+
+    #[error("unexpected {token} in member {member}")]
+    UnexpectedToken { token: String, member: String },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Word(String),
+    Dimension(String),
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Token::Word(w) => write!(f, "{}", w),
+            Token::Dimension(d) => write!(f, "[{}]", d),
+        }
+    }
+}
+
+impl FromStr for Member {
+    type Err = ParseMemberError;
+
+    // expects '<rand|randc (opt)> <type> <signing (opt)> <packed dims (opt)> <name> <unpacked dims (opt)> <= default (opt)>'
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let member = s.trim().to_string();
+        let (declaration, default) = split_default(&member)?;
+        let mut tokens = tokenize(declaration, &member)?.into_iter().peekable();
+
+        let mut is_randomized = false;
+        let mut is_cyclic = false;
+        match tokens.peek() {
+            Some(Token::Word(w)) if w == "rand" => is_randomized = true,
+            Some(Token::Word(w)) if w == "randc" => {
+                is_randomized = true;
+                is_cyclic = true;
+            }
+            _ => (),
+        }
+        if is_randomized {
+            tokens.next();
+        }
+
+        let base_kind = match tokens.next() {
+            Some(Token::Word(w)) if is_type_identifier(&w) => w,
+            Some(t) => {
+                return Err(Self::Err::InvalidIdentifier {
+                    identifier: t.to_string(),
+                    member,
+                })
+            }
+            None => return Err(Self::Err::InvalidMemberDescription(member)),
+        };
+
+        let signing = match tokens.peek() {
+            Some(Token::Word(w)) if w == "signed" || w == "unsigned" => {
+                let signing = w.clone();
+                tokens.next();
+                if !is_integral_keyword(&base_kind) {
+                    return Err(Self::Err::UnexpectedSigning {
+                        signing,
+                        kind: base_kind,
+                        member,
+                    });
+                }
+                Some(signing)
+            }
+            _ => None,
+        };
+
+        let mut packed_dimensions = Vec::new();
+        while let Some(Token::Dimension(d)) = tokens.peek() {
+            let d = d.replace(' ', "");
+            tokens.next();
+            let is_packable =
+                INTEGER_VECTOR_TYPES.contains(&base_kind.as_str()) || !is_keyword_type(&base_kind);
+            if !is_packable || !d.contains(':') || d.contains('$') {
+                return Err(Self::Err::InvalidPackedDimension {
+                    dimension: format!("[{}]", d),
+                    kind: base_kind,
+                    member,
+                });
+            }
+            packed_dimensions.push(format!("[{}]", d));
+        }
+
+        let name = match tokens.next() {
+            Some(Token::Word(w)) if is_identifier(&w) => w,
+            Some(t) => {
+                return Err(Self::Err::InvalidIdentifier {
+                    identifier: t.to_string(),
+                    member,
+                })
+            }
+            None => return Err(Self::Err::InvalidMemberDescription(member)),
+        };
+
+        let mut unpacked_dimensions = Vec::new();
+        for t in tokens {
+            match t {
+                Token::Dimension(d) => {
+                    let dimension = d.parse::<UnpackedDimension>().map_err(|_| {
+                        Self::Err::InvalidUnpackedDimension {
+                            dimension: format!("[{}]", d),
+                            member: member.clone(),
+                        }
+                    })?;
+                    unpacked_dimensions.push(dimension);
+                }
+                Token::Word(w) => {
+                    return Err(Self::Err::UnexpectedToken { token: w, member });
+                }
+            }
+        }
+
+        let mut kind = base_kind.clone();
+        if let Some(signing) = &signing {
+            kind = format!("{} {}", kind, signing);
+        }
+        if !packed_dimensions.is_empty() {
+            kind = format!("{} {}", kind, packed_dimensions.join(""));
+        }
+
+        let mut member = Member {
+            name,
+            kind,
+            base_kind,
+            signing,
+            packed_dimensions,
+            unpacked_dimensions,
+            default,
+            is_randomized,
+            is_cyclic,
+            fields: Vec::new(),
+            is_compared: true,
+            is_packed: true,
+            is_printed: true,
+            is_recorded: true,
+        };
+        let scope = TypeScope {
+            enums: &[],
+            types: &[],
+        };
+        member.fields = scope.get_fields(&member);
+
+        Ok(member)
+    }
+}
+
+impl TryFrom<&Membercfg> for Member {
+    type Error = ParseMemberError;
+
+    fn try_from(member: &Membercfg) -> Result<Self, Self::Error> {
+        match member {
+            Membercfg::Declaration(s) => s.parse(),
+            Membercfg::Table(t) => {
+                let mut member: Member = t.decl.parse()?;
+                member.is_compared = t.compare;
+                member.is_packed = t.pack;
+                member.is_printed = t.print;
+                member.is_recorded = t.record;
+                Ok(member)
+            }
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(try_from = "String", into = "String")]
+pub enum UnpackedDimension {
+    // '[<size>]' or '[<msb>:<lsb>]'
+    Fixed(String),
+    // '[]'
+    Dynamic,
+    // '[$]' or '[$:<max>]'
+    Queue(Option<String>),
+    // '[*]' or '[<index type>]'
+    Associative(String),
+}
+
+impl std::fmt::Display for UnpackedDimension {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            UnpackedDimension::Fixed(size) => write!(f, "[{}]", size),
+            UnpackedDimension::Dynamic => write!(f, "[]"),
+            UnpackedDimension::Queue(Some(max)) => write!(f, "[$:{}]", max),
+            UnpackedDimension::Queue(None) => write!(f, "[$]"),
+            UnpackedDimension::Associative(index) => write!(f, "[{}]", index),
+        }
+    }
+}
+
+impl FromStr for UnpackedDimension {
+    type Err = ParseMemberError;
+
+    // expects the dimension with or without its brackets, e.g. '[$:15]' or '$:15'
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let d = s.trim();
+        let d = d
+            .strip_prefix('[')
+            .and_then(|d| d.strip_suffix(']'))
+            .unwrap_or(d);
+        let words: Vec<&str> = d.split_whitespace().collect();
+        let d: String = words.concat();
+        let invalid = || Self::Err::InvalidUnpackedDimension {
+            dimension: s.to_string(),
+            member: String::new(),
+        };
+
+        if let [kind, signing] = words[..] {
+            // signed or unsigned integral index, e.g. 'int unsigned'
+            if is_integral_keyword(kind) && (signing == "signed" || signing == "unsigned") {
+                return Ok(UnpackedDimension::Associative(words.join(" ")));
+            }
+        }
+
+        // words only separated by spaces cannot form a size, e.g. '[int int]'
+        let is_word_char = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '$';
+        if words
+            .windows(2)
+            .any(|w| w[0].ends_with(is_word_char) && w[1].starts_with(is_word_char))
+        {
+            return Err(invalid());
+        }
+
+        if d.is_empty() {
+            Ok(UnpackedDimension::Dynamic)
+        } else if d == "$" {
+            Ok(UnpackedDimension::Queue(None))
+        } else if let Some(max) = d.strip_prefix("$:") {
+            if max.is_empty() || max.contains('$') {
+                Err(invalid())
+            } else {
+                Ok(UnpackedDimension::Queue(Some(max.to_string())))
+            }
+        } else if d.contains('$') || d.contains('[') || d.contains(']') {
+            Err(invalid())
+        } else if d == "*" || is_keyword_type(&d) {
+            Ok(UnpackedDimension::Associative(d))
+        } else {
+            Ok(UnpackedDimension::Fixed(d))
+        }
+    }
+}
+
+impl TryFrom<String> for UnpackedDimension {
+    type Error = ParseMemberError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<UnpackedDimension> for String {
+    fn from(d: UnpackedDimension) -> Self {
+        d.to_string()
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct EnumType {
+    pub name: String,
+    pub base: Option<String>,
+    pub values: Vec<EnumValue>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct EnumValue {
+    pub name: String,
+    pub value: Option<String>,
+}
+
+impl std::fmt::Display for EnumValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &self.value {
+            Some(value) => write!(f, "{} = {}", self.name, value),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ParseEnumError {
+    #[error("invalid enum name {0}")]
+    InvalidEnumName(String),
+
+    #[error("enum {0} has no value")]
+    EmptyEnum(String),
+
+    #[error("invalid value {value} in enum {name} (expected: '<NAME>' or '<NAME> = <value>')")]
+    InvalidEnumValue { name: String, value: String },
+
+    #[error("value {value} declared several times in enum {name}")]
+    DuplicatedEnumValue { name: String, value: String },
+
+    #[error("invalid base type {base} for enum {name} (expected an integral type, e.g. 'bit [1:0]' or 'int unsigned')")]
+    InvalidEnumBase { name: String, base: String },
+}
+
+impl TryFrom<&Enumcfg> for EnumType {
+    type Error = ParseEnumError;
+
+    fn try_from(e: &Enumcfg) -> Result<Self, Self::Error> {
+        if !is_identifier(&e.name) {
+            return Err(Self::Error::InvalidEnumName(e.name.clone()));
+        }
+        if e.values.is_empty() {
+            return Err(Self::Error::EmptyEnum(e.name.clone()));
+        }
+
+        let base = match &e.base {
+            Some(base) => {
+                let invalid = || Self::Error::InvalidEnumBase {
+                    name: e.name.clone(),
+                    base: base.clone(),
+                };
+                let member = format!("{} __base__", base)
+                    .parse::<Member>()
+                    .map_err(|_| invalid())?;
+                if !is_integral_keyword(&member.base_kind) {
+                    return Err(invalid());
+                }
+                Some(member.kind)
+            }
+            None => None,
+        };
+
+        let mut values: Vec<EnumValue> = Vec::new();
+        for v in &e.values {
+            let (name, value) = match v.split_once('=') {
+                Some((name, value)) => (name.trim(), Some(value.trim())),
+                None => (v.trim(), None),
+            };
+            if !is_identifier(name) || value.is_some_and(|value| value.is_empty()) {
+                return Err(Self::Error::InvalidEnumValue {
+                    name: e.name.clone(),
+                    value: v.clone(),
+                });
+            }
+            if values.iter().any(|v| v.name == name) {
+                return Err(Self::Error::DuplicatedEnumValue {
+                    name: e.name.clone(),
+                    value: name.to_string(),
+                });
+            }
+            values.push(EnumValue {
+                name: name.to_string(),
+                value: value.map(|value| value.to_string()),
+            });
+        }
+
+        Ok(EnumType {
+            name: e.name.clone(),
+            base,
+            values,
+        })
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct UserType {
+    pub name: String,
+    pub kind: TypeKind,
+    pub packed: bool,
+    pub fields: Vec<Member>,
+    pub alias: Option<Member>,
+}
+
+#[derive(Debug, Error)]
+pub enum ParseTypeError {
+    #[error("invalid type name {0}")]
+    InvalidTypeName(String),
+
+    #[error("{0} has no field")]
+    MissingFields(String),
+
+    #[error("alias {0} does not take fields")]
+    UnexpectedFields(String),
+
+    #[error("alias {0} has no aliased type (expected: type = \"<type>\")")]
+    MissingAlias(String),
+
+    #[error("{0} is not an alias, type not allowed")]
+    UnexpectedAlias(String),
+
+    #[error("invalid field in type {name}: {source}")]
+    InvalidField {
+        name: String,
+        source: ParseMemberError,
+    },
+
+    #[error("field {field} declared several times in type {name}")]
+    DuplicatedField { name: String, field: String },
+
+    #[error("field {field} of packed type {name} must be integral, without unpacked dimensions, randomization or default value")]
+    InvalidPackedField { name: String, field: String },
+
+    #[error("type {used} used in {name} before its declaration")]
+    UsedBeforeDeclaration { name: String, used: String },
+
+    #[error("fields of packed union {0} must have the same width")]
+    UnionWidthMismatch(String),
+}
+
+impl TryFrom<&Typecfg> for UserType {
+    type Error = ParseTypeError;
+
+    fn try_from(t: &Typecfg) -> Result<Self, Self::Error> {
+        if !is_identifier(&t.name) {
+            return Err(Self::Error::InvalidTypeName(t.name.clone()));
+        }
+
+        let alias = match (&t.kind, &t.alias) {
+            (TypeKind::Alias, Some(alias)) => {
+                if !t.fields.is_empty() {
+                    return Err(Self::Error::UnexpectedFields(t.name.clone()));
+                }
+                let alias =
+                    format!("{} {}", alias, t.name)
+                        .parse::<Member>()
+                        .map_err(|source| Self::Error::InvalidField {
+                            name: t.name.clone(),
+                            source,
+                        })?;
+                Some(alias)
+            }
+            (TypeKind::Alias, None) => return Err(Self::Error::MissingAlias(t.name.clone())),
+            (_, Some(_)) => return Err(Self::Error::UnexpectedAlias(t.name.clone())),
+            (_, None) => None,
+        };
+
+        let mut fields: Vec<Member> = Vec::new();
+        for f in &t.fields {
+            let field = f
+                .parse::<Member>()
+                .map_err(|source| Self::Error::InvalidField {
+                    name: t.name.clone(),
+                    source,
+                })?;
+            if fields.iter().any(|f| f.name == field.name) {
+                return Err(Self::Error::DuplicatedField {
+                    name: t.name.clone(),
+                    field: field.name,
+                });
+            }
+            fields.push(field);
+        }
+        if t.kind != TypeKind::Alias && fields.is_empty() {
+            return Err(Self::Error::MissingFields(t.name.clone()));
+        }
+
+        Ok(UserType {
+            name: t.name.clone(),
+            kind: t.kind.clone(),
+            packed: t.packed,
+            fields,
+            alias,
+        })
+    }
+}
+
+impl UserType {
+    // checks fields against the types declared so far in scope, 'declared' holds all VIP types names
+    pub fn check(&self, scope: &TypeScope, declared: &[String]) -> Result<(), ParseTypeError> {
+        for m in self.fields.iter().chain(self.alias.iter()) {
+            let used = &m.base_kind;
+            if declared.contains(used) && scope.get_type(used).is_none() {
+                return Err(ParseTypeError::UsedBeforeDeclaration {
+                    name: self.name.clone(),
+                    used: used.clone(),
+                });
+            }
+        }
+
+        if self.kind == TypeKind::Alias || !self.packed {
+            return Ok(());
+        }
+
+        for f in &self.fields {
+            let is_unpacked_type = scope
+                .get_type(&f.base_kind)
+                .is_some_and(|t| t.kind != TypeKind::Alias && !t.packed);
+            let is_non_integral_keyword =
+                is_keyword_type(&f.base_kind) && !is_integral_keyword(&f.base_kind);
+            if f.is_randomized
+                || f.default.is_some()
+                || !f.unpacked_dimensions.is_empty()
+                || is_unpacked_type
+                || is_non_integral_keyword
+            {
+                return Err(ParseTypeError::InvalidPackedField {
+                    name: self.name.clone(),
+                    field: f.name.clone(),
+                });
+            }
+        }
+
+        if self.kind == TypeKind::Union {
+            let widths: Vec<Option<u32>> = self.fields.iter().map(|f| scope.get_width(f)).collect();
+            if widths.iter().all(|w| w.is_some()) && widths.windows(2).any(|w| w[0] != w[1]) {
+                return Err(ParseTypeError::UnionWidthMismatch(self.name.clone()));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// enums and user types visible from a VIP package
+pub struct TypeScope<'a> {
+    pub enums: &'a [EnumType],
+    pub types: &'a [UserType],
+}
+
+impl TypeScope<'_> {
+    pub fn get_type(&self, name: &str) -> Option<&UserType> {
+        self.types.iter().find(|t| t.name == name)
+    }
+
+    pub fn get_enum(&self, name: &str) -> Option<&EnumType> {
+        self.enums.iter().find(|e| e.name == name)
+    }
+
+    pub fn is_declared(&self, name: &str) -> bool {
+        self.get_type(name).is_some() || self.get_enum(name).is_some()
+    }
+
+    // flattens structs into their leaves, arrays and unions are kept whole
+    pub fn get_fields(&self, member: &Member) -> Vec<MemberField> {
+        self.get_fields_at(&member.name, member)
+    }
+
+    fn get_fields_at(&self, path: &str, member: &Member) -> Vec<MemberField> {
+        if member.unpacked_dimensions.is_empty() && member.packed_dimensions.is_empty() {
+            match self.get_type(&member.base_kind) {
+                Some(UserType {
+                    kind: TypeKind::Struct,
+                    fields,
+                    ..
+                }) => {
+                    return fields
+                        .iter()
+                        .flat_map(|f| self.get_fields_at(&format!("{}.{}", path, f.name), f))
+                        .collect()
+                }
+                Some(UserType {
+                    kind: TypeKind::Alias,
+                    alias: Some(alias),
+                    ..
+                }) => {
+                    let mut fields = self.get_fields_at(path, alias);
+                    if let [f] = &mut fields[..] {
+                        f.kind = member.kind.clone();
+                    }
+                    return fields;
+                }
+                _ => (),
+            }
+        }
+
+        let (category, enum_kind) = self.get_category(member);
+        let array = match category {
+            TypeCategory::Array => Some(self.get_array_field(path, member)),
+            _ => None,
+        };
+        let enum_kind = match &array {
+            Some(array) if array.element_category == TypeCategory::Enum => {
+                let mut element = member.clone();
+                element.unpacked_dimensions.clear();
+                self.get_category(&element).1
+            }
+            _ => enum_kind,
+        };
+
+        vec![MemberField {
+            path: path.to_string(),
+            kind: member.kind.clone(),
+            category,
+            enum_kind,
+            array,
+        }]
+    }
+
+    pub fn get_category(&self, member: &Member) -> (TypeCategory, Option<String>) {
+        if !member.unpacked_dimensions.is_empty() {
+            return (TypeCategory::Array, None);
+        }
+        if !member.packed_dimensions.is_empty() || is_integral_keyword(&member.base_kind) {
+            return (TypeCategory::Integral, None);
+        }
+        if NON_INTEGER_TYPES.contains(&member.base_kind.as_str()) {
+            return (TypeCategory::Real, None);
+        }
+        if member.base_kind == "string" {
+            return (TypeCategory::String, None);
+        }
+        if OTHER_TYPES.contains(&member.base_kind.as_str()) {
+            return (TypeCategory::Opaque, None);
+        }
+        if self.get_enum(&member.base_kind).is_some() {
+            return (TypeCategory::Enum, Some(member.base_kind.clone()));
+        }
+
+        match self.get_type(&member.base_kind) {
+            Some(UserType {
+                kind: TypeKind::Alias,
+                alias: Some(alias),
+                ..
+            }) => self.get_category(alias),
+            Some(t) if t.packed => (TypeCategory::Integral, None),
+            Some(_) => (TypeCategory::Opaque, None),
+            None => (TypeCategory::Object, None),
+        }
+    }
+
+    fn get_array_field(&self, path: &str, member: &Member) -> ArrayField {
+        let mut element = member.clone();
+        element.unpacked_dimensions.clear();
+        let element_category = match self.get_category(&element).0 {
+            TypeCategory::Array => TypeCategory::Opaque,
+            category => category,
+        };
+
+        let dimensions = &member.unpacked_dimensions;
+        let kind = match &dimensions[0] {
+            UnpackedDimension::Fixed(_) => ArrayKind::Fixed,
+            UnpackedDimension::Dynamic => ArrayKind::Dynamic,
+            UnpackedDimension::Queue(_) => ArrayKind::Queue,
+            UnpackedDimension::Associative(_) => ArrayKind::Associative,
+        };
+        let indices: Vec<String> = (0..dimensions.len()).map(|i| format!("i{}", i)).collect();
+        let element_name = dimensions
+            .iter()
+            .map(|d| match d {
+                UnpackedDimension::Associative(index) if index == "string" => "[%s]",
+                UnpackedDimension::Associative(index)
+                    if index != "*" && !is_integral_index(index) =>
+                {
+                    "[%p]"
+                }
+                _ => "[%0d]",
+            })
+            .collect::<String>();
+
+        let is_fixed = dimensions
+            .iter()
+            .all(|d| matches!(d, UnpackedDimension::Fixed(_)));
+        let is_packable = !matches!(element_category, TypeCategory::Opaque | TypeCategory::Array)
+            && (is_fixed || (dimensions.len() == 1 && kind != ArrayKind::Associative));
+
+        ArrayField {
+            kind,
+            indices: indices.join(", "),
+            element: format!("{}[{}]", path, indices.join("][")),
+            element_name: format!("{}{}", path, element_name),
+            element_kind: member.kind.clone(),
+            element_category,
+            is_packable,
+        }
+    }
+
+    // width of packed types, None when unknown or not packed
+    pub fn get_width(&self, member: &Member) -> Option<u32> {
+        if !member.unpacked_dimensions.is_empty() {
+            return None;
+        }
+        let mut width = self.get_base_width(&member.base_kind)?;
+        for d in &member.packed_dimensions {
+            width *= get_range_width(d)?;
+        }
+        Some(width)
+    }
+
+    fn get_base_width(&self, kind: &str) -> Option<u32> {
+        match kind {
+            "bit" | "logic" | "reg" => return Some(1),
+            "byte" => return Some(8),
+            "shortint" => return Some(16),
+            "int" | "integer" => return Some(32),
+            "longint" | "time" => return Some(64),
+            _ => (),
+        }
+
+        if let Some(e) = self.get_enum(kind) {
+            return match &e.base {
+                Some(base) => self.get_width(&format!("{} __base__", base).parse().ok()?),
+                None => Some(32),
+            };
+        }
+
+        let t = self.get_type(kind)?;
+        match t.kind {
+            TypeKind::Alias => self.get_width(t.alias.as_ref()?),
+            TypeKind::Struct if t.packed => t.fields.iter().map(|f| self.get_width(f)).sum(),
+            TypeKind::Union if t.packed => t
+                .fields
+                .iter()
+                .map(|f| self.get_width(f))
+                .collect::<Option<Vec<u32>>>()?
+                .into_iter()
+                .max(),
+            _ => None,
+        }
+    }
+}
+
+// expects '[<msb>:<lsb>]' with numeric bounds
+fn get_range_width(dimension: &str) -> Option<u32> {
+    let range = dimension.strip_prefix('[')?.strip_suffix(']')?;
+    let (end, start) = range.split_once(':')?;
+    let end = end.parse::<i64>().ok()?;
+    let start = start.parse::<i64>().ok()?;
+    u32::try_from(end.abs_diff(start) + 1).ok()
+}
+
+fn split_default(member: &str) -> Result<(&str, Option<String>), ParseMemberError> {
+    let mut depth = 0;
+    for (i, c) in member.char_indices() {
+        match c {
+            '[' => depth += 1,
+            ']' => depth -= 1,
+            '=' if depth == 0 => {
+                let default = member[i + 1..].trim();
+                if default.is_empty() {
+                    return Err(ParseMemberError::MissingDefault(member.to_string()));
+                }
+                return Ok((&member[..i], Some(default.to_string())));
+            }
+            _ => (),
+        }
+    }
+    Ok((member, None))
+}
+
+fn tokenize(declaration: &str, member: &str) -> Result<Vec<Token>, ParseMemberError> {
+    let mut tokens = Vec::new();
+    let mut chars = declaration.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() {
+            continue;
+        } else if c == '[' {
+            let mut dimension = String::new();
+            loop {
+                match chars.next() {
+                    Some(']') => break,
+                    Some('[') | None => {
+                        return Err(ParseMemberError::UnbalancedBrackets(member.to_string()))
+                    }
+                    Some(c) => dimension.push(c),
+                }
+            }
+            // words kept apart for multi-word index types, e.g. '[int unsigned]'
+            let dimension: Vec<&str> = dimension.split_whitespace().collect();
+            tokens.push(Token::Dimension(dimension.join(" ")));
+        } else if c == ']' {
+            return Err(ParseMemberError::UnbalancedBrackets(member.to_string()));
+        } else {
+            let mut word = c.to_string();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() || c == '[' || c == ']' {
+                    break;
+                }
+                word.push(c);
+                chars.next();
+            }
+            tokens.push(Token::Word(word));
+        }
+    }
+    Ok(tokens)
+}
+
+pub fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => (),
+        _ => return false,
+    }
+    chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+        && !KEYWORDS.contains(&s)
+        && !is_keyword_type(s)
+}
+
+// accepts keyword types and (package scoped) user types, e.g. 'my_pkg::header_t'
+fn is_type_identifier(s: &str) -> bool {
+    is_keyword_type(s) || s.split("::").all(is_identifier)
+}
+
+fn is_keyword_type(s: &str) -> bool {
+    INTEGER_VECTOR_TYPES.contains(&s)
+        || INTEGER_ATOM_TYPES.contains(&s)
+        || NON_INTEGER_TYPES.contains(&s)
+        || OTHER_TYPES.contains(&s)
+}
+
+// integral associative array index, possibly signed or unsigned
+fn is_integral_index(s: &str) -> bool {
+    s.split_whitespace().next().is_some_and(is_integral_keyword)
+}
+
+fn is_integral_keyword(s: &str) -> bool {
+    INTEGER_VECTOR_TYPES.contains(&s) || INTEGER_ATOM_TYPES.contains(&s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ArrayKind, EnumType, Member, TypeCategory, TypeScope, UnpackedDimension, UserType,
+    };
+    use crate::config::vip::{
+        Enum as Enumcfg, Member as Membercfg, MemberTable, Type as Typecfg, TypeKind,
+    };
+
+    #[test]
+    fn successful_members_parsing() {
+        let descriptions = vec![
+            ("__type__ __name__", "__type__", "__name__", false, false),
+            (
+                "rand __type__ __name__",
+                "__type__",
+                "__name__",
+                true,
+                false,
+            ),
+            ("bit is_valid", "bit", "is_valid", false, false),
+            (
+                "rand bit[24:16] is_ready",
+                "bit [24:16]",
+                "is_ready",
+                true,
+                false,
+            ),
+            ("rand bit is_ready", "bit", "is_ready", true, false),
+            ("  rand   bit   is_ready  ", "bit", "is_ready", true, false),
+            ("rand bit [31:2] addr", "bit [31:2]", "addr", true, false),
+            (
+                "rand bit [ 31 : 2 ] addr",
+                "bit [31:2]",
+                "addr",
+                true,
+                false,
+            ),
+            ("randc logic [7:0] id", "logic [7:0]", "id", true, true),
+            ("rand byte payload[]", "byte", "payload", true, false),
+            ("int unsigned len", "int unsigned", "len", false, false),
+            ("string tag", "string", "tag", false, false),
+            (
+                "logic signed [3:0][7:0] word",
+                "logic signed [3:0][7:0]",
+                "word",
+                false,
+                false,
+            ),
+            (
+                "rand my_pkg::header_t hdr",
+                "my_pkg::header_t",
+                "hdr",
+                true,
+                false,
+            ),
+            ("real ratio = 0.5", "real", "ratio", false, false),
+        ];
+
+        for (d, kind, name, is_randomized, is_cyclic) in descriptions {
+            let parsed_member = d.parse::<Member>().unwrap();
+            assert_eq!(parsed_member.kind, kind);
+            assert_eq!(parsed_member.name, name);
+            assert_eq!(parsed_member.is_randomized, is_randomized);
+            assert_eq!(parsed_member.is_cyclic, is_cyclic);
+        }
+    }
+
+    #[test]
+    fn members_unpacked_dimensions_and_defaults() {
+        let member = "rand bit [7:0] data [4][$:15][string] = '{default: 0}"
+            .parse::<Member>()
+            .unwrap();
+        assert_eq!(
+            member.unpacked_dimensions,
+            vec![
+                UnpackedDimension::Fixed("4".to_string()),
+                UnpackedDimension::Queue(Some("15".to_string())),
+                UnpackedDimension::Associative("string".to_string()),
+            ]
+        );
+        assert_eq!(member.default, Some("'{default: 0}".to_string()));
+
+        let member = "int q[$] [*] [0:3]".parse::<Member>().unwrap();
+        assert_eq!(
+            member.unpacked_dimensions,
+            vec![
+                UnpackedDimension::Queue(None),
+                UnpackedDimension::Associative("*".to_string()),
+                UnpackedDimension::Fixed("0:3".to_string()),
+            ]
+        );
+
+        let member = "bit [ 7 : 0 ] m [int unsigned] [ 0 : 3 ] [ $ : 7 ]"
+            .parse::<Member>()
+            .unwrap();
+        assert_eq!(member.kind, "bit [7:0]");
+        assert_eq!(
+            member.unpacked_dimensions,
+            vec![
+                UnpackedDimension::Associative("int unsigned".to_string()),
+                UnpackedDimension::Fixed("0:3".to_string()),
+                UnpackedDimension::Queue(Some("7".to_string())),
+            ]
+        );
+        assert!("int m [int int]".parse::<Member>().is_err());
+        assert!("int m [string unsigned]".parse::<Member>().is_err());
+    }
+
+    #[test]
+    fn failed_members_parsing() {
+        let descriptions = vec![
+            "",
+            "    ",
+            "__not_rand__ __type__ __name__",
+            "__name__",
+            "a b c d",
+            "rand randc bit is_ready",
+            "rand bit [31:2 is_ready",
+            "rand bit 31:2] is_ready",
+            "bit [7] data",
+            "int [7:0] data",
+            "string signed tag",
+            "rand bit data [$$]",
+            "bit data =",
+            "bit int",
+            "bit 2data",
+            "bit data [3] extra",
+        ];
+
+        for d in descriptions {
+            let parsed_member = d.parse::<Member>();
+            assert!(parsed_member.is_err(), "{}", d);
+        }
+    }
+
+    #[test]
+    fn enums_parsing() {
+        let enum_cfg = |base: Option<&str>, values: Vec<&str>| Enumcfg {
+            name: "opcode_e".to_string(),
+            values: values.into_iter().map(|v| v.to_string()).collect(),
+            base: base.map(|b| b.to_string()),
+        };
+
+        let e =
+            EnumType::try_from(&enum_cfg(Some("bit [1:0]"), vec!["READ", "WRITE = 2"])).unwrap();
+        assert_eq!(e.base, Some("bit [1:0]".to_string()));
+        assert_eq!(e.values[1].value, Some("2".to_string()));
+
+        let failing = vec![
+            enum_cfg(None, vec![]),
+            enum_cfg(None, vec!["READ", "READ"]),
+            enum_cfg(None, vec!["READ ="]),
+            enum_cfg(None, vec!["2READ"]),
+            enum_cfg(Some("string"), vec!["READ"]),
+            enum_cfg(Some("bit [1:0] [3]"), vec!["READ"]),
+        ];
+        for e in failing {
+            assert!(EnumType::try_from(&e).is_err(), "{:?}", e);
+        }
+    }
+
+    fn type_cfg(name: &str, kind: TypeKind, fields: Vec<&str>, alias: Option<&str>) -> Typecfg {
+        Typecfg {
+            name: name.to_string(),
+            kind,
+            fields: fields.into_iter().map(|f| f.to_string()).collect(),
+            alias: alias.map(|a| a.to_string()),
+            packed: true,
+        }
+    }
+
+    #[test]
+    fn types_fields_and_widths() {
+        let enums = vec![EnumType::try_from(&Enumcfg {
+            name: "opcode_e".to_string(),
+            values: vec!["READ".to_string(), "WRITE".to_string()],
+            base: Some("bit [1:0]".to_string()),
+        })
+        .unwrap()];
+        let mut types = Vec::new();
+        for t in [
+            type_cfg("addr_t", TypeKind::Alias, vec![], Some("bit [15:0]")),
+            type_cfg(
+                "header_t",
+                TypeKind::Struct,
+                vec!["bit [3:0] len", "opcode_e op", "addr_t addr"],
+                None,
+            ),
+            type_cfg(
+                "data_u",
+                TypeKind::Union,
+                vec!["header_t hdr", "bit [21:0] raw"],
+                None,
+            ),
+        ] {
+            types.push(UserType::try_from(&t).unwrap());
+        }
+        let scope = TypeScope {
+            enums: &enums,
+            types: &types,
+        };
+
+        let member = "rand header_t hdr".parse::<Member>().unwrap();
+        let fields = scope.get_fields(&member);
+        let paths: Vec<&str> = fields.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["hdr.len", "hdr.op", "hdr.addr"]);
+        assert_eq!(fields[1].enum_kind, Some("opcode_e".to_string()));
+        assert_eq!(scope.get_width(&member), Some(22));
+
+        let member = "data_u d".parse::<Member>().unwrap();
+        assert_eq!(scope.get_fields(&member).len(), 1);
+        assert_eq!(scope.get_width(&member), Some(22));
+
+        let member = "header_t [1:0] hdrs".parse::<Member>().unwrap();
+        assert_eq!(scope.get_width(&member), Some(44));
+        assert_eq!(scope.get_width(&"string s".parse().unwrap()), None);
+
+        let invalid = vec![
+            type_cfg("bad_t", TypeKind::Struct, vec![], None),
+            type_cfg("bad_t", TypeKind::Alias, vec![], None),
+            type_cfg("bad_t", TypeKind::Struct, vec!["bit a"], Some("bit")),
+            type_cfg("bad_t", TypeKind::Struct, vec!["bit a", "int a"], None),
+            type_cfg("bad_t", TypeKind::Union, vec!["bit a", "2b"], None),
+        ];
+        for t in invalid {
+            assert!(UserType::try_from(&t).is_err(), "{:?}", t);
+        }
+
+        let declared = vec!["later_t".to_string()];
+        let unchecked = vec![
+            type_cfg("bad_t", TypeKind::Struct, vec!["string s"], None),
+            type_cfg("bad_t", TypeKind::Struct, vec!["rand bit a"], None),
+            type_cfg("bad_t", TypeKind::Struct, vec!["later_t a"], None),
+            type_cfg("bad_t", TypeKind::Union, vec!["bit a", "byte b"], None),
+        ];
+        for t in unchecked {
+            let t = UserType::try_from(&t).unwrap();
+            assert!(t.check(&scope, &declared).is_err(), "{:?}", t);
+        }
+    }
+
+    #[test]
+    fn members_categories() {
+        let scope = TypeScope {
+            enums: &[],
+            types: &[],
+        };
+        let categories = vec![
+            ("bit [7:0] a", TypeCategory::Integral),
+            ("int unsigned a", TypeCategory::Integral),
+            ("real a", TypeCategory::Real),
+            ("string a", TypeCategory::String),
+            ("my_class a", TypeCategory::Object),
+            ("chandle a", TypeCategory::Opaque),
+            ("byte a []", TypeCategory::Array),
+        ];
+        for (d, category) in categories {
+            let member = d.parse::<Member>().unwrap();
+            assert_eq!(scope.get_category(&member).0, category, "{}", d);
+            assert_eq!(member.fields[0].category, category, "{}", d);
+        }
+
+        let arrays = vec![
+            ("byte a []", ArrayKind::Dynamic, "a[%0d]", true),
+            ("string a [$]", ArrayKind::Queue, "a[%0d]", true),
+            ("int a [4][2]", ArrayKind::Fixed, "a[%0d][%0d]", true),
+            ("int a [string]", ArrayKind::Associative, "a[%s]", false),
+            (
+                "int a [int unsigned]",
+                ArrayKind::Associative,
+                "a[%0d]",
+                false,
+            ),
+            ("int a [][2]", ArrayKind::Dynamic, "a[%0d][%0d]", false),
+            ("chandle a [2]", ArrayKind::Fixed, "a[%0d]", false),
+        ];
+        for (d, kind, element_name, is_packable) in arrays {
+            let member = d.parse::<Member>().unwrap();
+            let array = member.fields[0].array.clone().unwrap();
+            assert_eq!(array.kind, kind, "{}", d);
+            assert_eq!(array.element_name, element_name, "{}", d);
+            assert_eq!(array.is_packable, is_packable, "{}", d);
+        }
+    }
+
+    #[test]
+    fn members_policies() {
+        let member =
+            Member::try_from(&Membercfg::Declaration("rand int rate".to_string())).unwrap();
+        assert!(member.is_compared && member.is_packed && member.is_printed && member.is_recorded);
+
+        let table = MemberTable {
+            decl: "time stamp".to_string(),
+            compare: false,
+            pack: false,
+            print: true,
+            record: true,
+        };
+        let member = Member::try_from(&Membercfg::Table(table)).unwrap();
+        assert!(!member.is_compared && !member.is_packed);
+        assert!(member.is_printed && member.is_recorded);
+    }
+}
@@ -1,7 +1,14 @@
+pub mod constraint;
+pub mod coverage;
+pub mod mapping;
+pub mod member;
+pub mod parameter;
+pub mod protocol;
+pub mod sequence;
+pub mod timing;
 pub mod top;
 pub mod vip;
 
-use std::collections::HashMap;
 use std::error::Error;
 use std::fs::File;
 use std::io::Write;
@@ -11,12 +18,18 @@ use tera::Tera;
 
 use crate::cli::Args;
 use crate::config::{instance::Instances, project::Project};
+use crate::dut::utils::DUT;
 
-use top::Top;
+use timing::{get_render_timescale, Timescale};
+use top::{
+    get_dut_clk_rst_ports, get_dut_tie_offs, get_dut_wires, get_harness_clocks, get_reset_vips,
+    get_vips_clk_rst_ports, Top,
+};
 use vip::VIP;
 
+#[allow(clippy::upper_case_acronyms)]
 enum Mode {
-    VIP(VIP),
+    VIP(Box<VIP>, Timescale),
     Top(Top, Vec<VIP>, Instances),
     STTop(Top, Vec<VIP>, Instances),
     TopTest(Top, Vec<VIP>, Instances),
@@ -29,7 +42,7 @@ enum Mode {
 impl Mode {
     fn get_components(&self) -> Vec<String> {
         match self {
-            Mode::VIP(_) => {
+            Mode::VIP(_, _) => {
                 vec![
                     "agent".to_string(),
                     "config".to_string(),
@@ -72,7 +85,7 @@ impl Mode {
 
     fn get_output_directory_path(&self, cli: &Args) -> String {
         match self {
-            Mode::VIP(vip) => format!("{}/vip/{}", cli.output.clone(), vip.name),
+            Mode::VIP(vip, _) => format!("{}/vip/{}", cli.output.clone(), vip.name),
             Mode::Top(top, _, _) | Mode::STTop(top, _, _) => {
                 format!("{}/{}", cli.output.clone(), top.name)
             }
@@ -86,7 +99,7 @@ impl Mode {
 
     fn get_output_filename(&self, component: String) -> String {
         match self {
-            Mode::VIP(vip) => format!("{}_{}.sv", vip.name, component),
+            Mode::VIP(vip, _) => format!("{}_{}.sv", vip.name, component),
             Mode::Top(top, _, _) | Mode::STTop(top, _, _) => {
                 format!("{}_{}.sv", top.name, component)
             }
@@ -101,7 +114,7 @@ impl Mode {
 
     fn get_template_path(&self, component: String) -> String {
         match self {
-            Mode::VIP(_) => format!("vip/{}.sv.j2", component),
+            Mode::VIP(_, _) => format!("vip/{}.sv.j2", component),
             Mode::Top(_, _, _) => format!("top/{}.sv.j2", component),
             Mode::STTop(_, _, _) => {
                 let name = if component == "scoreboard" {
@@ -128,13 +141,15 @@ impl Mode {
     fn get_context(&self) -> tera::Context {
         let mut context = tera::Context::new();
         match self {
-            Mode::VIP(vip) => {
+            Mode::VIP(vip, timescale) => {
                 context.insert("vip", &vip);
+                context.insert("timescale", &timescale);
             }
             Mode::Top(top, vips, instances) | Mode::STTop(top, vips, instances) => {
                 context.insert("instances", &instances.instances);
                 context.insert("vips", &vips);
                 context.insert("top", &top);
+                context.insert("reset_vips", &get_reset_vips(vips));
             }
             Mode::TopTest(top, vips, instances) => {
                 context.insert("instances", &instances.instances);
@@ -145,20 +160,20 @@ impl Mode {
                 context.insert("instances", &instances.instances);
                 context.insert("top", &top);
 
-                let (vips_clk, vips_rst, vips_ports) = get_vips_clk_rst_ports(vips);
+                let (vips_clk, vips_rst) = get_vips_clk_rst_ports(vips);
                 context.insert("vips_clk", &vips_clk);
                 context.insert("vips_rst", &vips_rst);
-                context.insert("vips_ports", &vips_ports);
+                context.insert("harness_clocks", &get_harness_clocks(vips));
             }
             Mode::STTopTb(top, vips, instances) => {
                 context.insert("instances", &instances.instances);
                 context.insert("vips", &vips);
                 context.insert("top", &top);
 
-                let (vips_clk, vips_rst, vips_ports) = get_vips_clk_rst_ports(vips);
+                let (vips_clk, vips_rst) = get_vips_clk_rst_ports(vips);
                 context.insert("vips_clk", &vips_clk);
                 context.insert("vips_rst", &vips_rst);
-                context.insert("vips_ports", &vips_ports);
+                context.insert("harness_clocks", &get_harness_clocks(vips));
             }
             Mode::Bin(top, vips) | Mode::STBin(top, vips) => {
                 context.insert("vips", &vips);
@@ -169,39 +184,6 @@ impl Mode {
     }
 }
 
-fn get_vips_clk_rst_ports(
-    vips: &Vec<VIP>,
-) -> (
-    HashMap<String, String>,
-    HashMap<String, String>,
-    HashMap<String, Vec<String>>,
-) {
-    let mut vips_clk = HashMap::new();
-    for v in vips {
-        if let Some(clk) = v.clock.clone() {
-            vips_clk.insert(v.name.clone(), clk);
-        }
-    }
-
-    let mut vips_rst = HashMap::new();
-    for v in vips {
-        if let Some(rst) = v.reset.clone() {
-            vips_rst.insert(v.name.clone(), rst);
-        }
-    }
-
-    let mut vips_ports = HashMap::new();
-    for v in vips {
-        let mut ports = Vec::new();
-        for p in &v.ports {
-            ports.push(p.name.clone());
-        }
-        vips_ports.insert(v.name.clone(), ports);
-    }
-
-    (vips_clk, vips_rst, vips_ports)
-}
-
 fn render(mode: Mode, tera_dir: &Tera, cli: &Args) {
     let components = mode.get_components();
 
@@ -245,9 +227,13 @@ pub fn render_self_test(
     let top = Top {
         name,
         default_sequence_repeat: project.top_default_sequence,
+        timescale: get_render_timescale(&project.timescale),
         dut_name: "".to_string(),
         dut_clk: None,
         dut_rst: None,
+        dut_clk_rst: Vec::new(),
+        dut_wires: Vec::new(),
+        dut_tie_offs: Vec::new(),
     };
     let vips = vec![vip.clone()];
 
@@ -264,34 +250,39 @@ pub fn render_self_test(
 
 pub fn render_top(
     tera_dir: &Tera,
-    vips: &Vec<VIP>,
+    vips: &[VIP],
     instances: &Instances,
     cli: &Args,
     project: &Project,
+    dut: &DUT,
 ) {
     let top = Top {
         name: "top".to_string(),
         default_sequence_repeat: project.top_default_sequence,
+        timescale: get_render_timescale(&project.timescale),
         dut_name: project.dut.name.clone().unwrap(),
         dut_clk: project.dut.clock.clone(),
         dut_rst: project.dut.reset.clone(),
+        dut_clk_rst: get_dut_clk_rst_ports(instances, vips, dut, &project.dut),
+        dut_wires: get_dut_wires(instances, dut),
+        dut_tie_offs: get_dut_tie_offs(instances, vips, dut, &project.dut),
     };
 
     let mut modes = Vec::new();
-    modes.push(Mode::Top(top.clone(), vips.clone(), instances.clone()));
-    modes.push(Mode::TopTest(top.clone(), vips.clone(), instances.clone()));
-    modes.push(Mode::TopTb(top.clone(), vips.clone(), instances.clone()));
-    modes.push(Mode::Bin(top.clone(), vips.clone()));
+    modes.push(Mode::Top(top.clone(), vips.to_vec(), instances.clone()));
+    modes.push(Mode::TopTest(top.clone(), vips.to_vec(), instances.clone()));
+    modes.push(Mode::TopTb(top.clone(), vips.to_vec(), instances.clone()));
+    modes.push(Mode::Bin(top.clone(), vips.to_vec()));
 
     for m in modes {
         render(m, tera_dir, cli);
     }
 }
 
-pub fn render_vips(tera_dir: &Tera, vips: &Vec<VIP>, cli: &Args) {
+pub fn render_vips(tera_dir: &Tera, vips: &Vec<VIP>, timescale: &Timescale, cli: &Args) {
     let mut modes = Vec::new();
     for v in vips {
-        modes.push(Mode::VIP(v.clone()));
+        modes.push(Mode::VIP(Box::new(v.clone()), timescale.clone()));
     }
 
     for m in modes {
@@ -313,3 +304,363 @@ pub fn get_tera_dir(cli: &Args) -> Tera {
 
     tera_dir
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use tera::Tera;
+
+    use super::{get_render_timescale, Mode};
+    use crate::config::instance::Instances;
+    use crate::config::project::Project;
+    use crate::config::vip::VIP as VIPcfg;
+    use crate::dut::utils::{Port, PortDirection, DUT};
+    use crate::render::top::{get_dut_clk_rst_ports, get_dut_tie_offs, get_dut_wires, Top};
+    use crate::render::vip::{get_render_vips, VIP};
+
+    fn get_templates() -> Tera {
+        let templates = concat!(env!("CARGO_MANIFEST_DIR"), "/templates/**/*.j2");
+        let mut tera = Tera::new(templates).unwrap();
+        tera.autoescape_on(vec![]);
+        tera
+    }
+
+    fn get_dut(ports: &[(&str, PortDirection)]) -> DUT {
+        let mut dut_ports = HashMap::new();
+        for (description, direction) in ports {
+            let mut port = description.parse::<Port>().unwrap();
+            port.properties.direction = direction.clone();
+            dut_ports.insert(port.name, port.properties);
+        }
+        DUT {
+            name: "fifo".to_string(),
+            ports: dut_ports,
+        }
+    }
+
+    fn render_tb(
+        template: &str,
+        vips: &[&str],
+        instances: &str,
+        project: &str,
+        dut: &DUT,
+    ) -> String {
+        let vips: Vec<VIPcfg> = vips.iter().map(|v| toml::from_str(v).unwrap()).collect();
+        let vips: Vec<VIP> = get_render_vips(&vips);
+        let mut instances: Instances = toml::from_str(instances).unwrap();
+        let project: Project = toml::from_str(project).unwrap();
+        instances.estimate_ids();
+        instances.set_connections(&vips, true);
+        instances.set_specializations(&vips);
+        instances.set_harness_assigns(&vips, dut);
+
+        let top = Top {
+            name: "top".to_string(),
+            default_sequence_repeat: project.top_default_sequence,
+            timescale: get_render_timescale(&project.timescale),
+            dut_name: dut.name.clone(),
+            dut_clk: project.dut.clock.clone(),
+            dut_rst: project.dut.reset.clone(),
+            dut_clk_rst: get_dut_clk_rst_ports(&instances, &vips, dut, &project.dut),
+            dut_wires: get_dut_wires(&instances, dut),
+            dut_tie_offs: get_dut_tie_offs(&instances, &vips, dut, &project.dut),
+        };
+        let mode = Mode::TopTb(top, vips, instances);
+        get_templates()
+            .render(template, &mode.get_context())
+            .unwrap()
+    }
+
+    fn render_vip(template: &str, vip: &str) -> String {
+        let vip: VIPcfg = toml::from_str(vip).unwrap();
+        let vip = get_render_vips(&vec![vip]).remove(0);
+        let project: Project = toml::from_str("").unwrap();
+        let mode = Mode::VIP(Box::new(vip), get_render_timescale(&project.timescale));
+        get_templates()
+            .render(template, &mode.get_context())
+            .unwrap()
+    }
+
+    #[test]
+    fn harness_constant_connections() {
+        let vip = r#"
+            name = "fifo_16"
+            ports = ["data [15:0]", "data_vld", "data_rdy"]
+            clock = "clk"
+        "#;
+        let instances = r#"
+            [[instances]]
+            vip_name = "fifo_16"
+            connected_to = ["data_out", "data_out_vld", "1'b1"]
+            mode = "Responder"
+        "#;
+        let dut = get_dut(&[
+            ("data_out [15:0]", PortDirection::OUTPUT),
+            ("data_out_vld", PortDirection::OUTPUT),
+            ("data_out_rdy", PortDirection::INPUT),
+        ]);
+
+        let th = render_tb("top/tb/th.sv.j2", &[vip], instances, "", &dut);
+        // no wire without sliced DUT port, the constant is still assigned
+        assert!(!th.contains("wire"));
+        assert!(th.contains("assign fifo_16_resp_0_if.data_rdy = 1'b1;"));
+        assert!(th.contains(".data_out (fifo_16_resp_0_if.data)"));
+        assert!(th.contains(".data_out_rdy ('0)"));
+    }
+
+    #[test]
+    fn harness_partial_tie_offs() {
+        let vip = r#"
+            name = "irq"
+            ports = ["irq [3:0]"]
+        "#;
+        let instances = r#"
+            [[instances]]
+            vip_name = "irq"
+            connected_to = ["irq[3:0]"]
+        "#;
+        let project = r#"
+            [dut.tie_offs]
+            irq = "8'hA5"
+            mask = "4'b0011"
+        "#;
+        let dut = get_dut(&[
+            ("irq [7:0]", PortDirection::INPUT),
+            ("mask [3:0]", PortDirection::INPUT),
+        ]);
+
+        let th = render_tb("top/tb/th.sv.j2", &[vip], instances, project, &dut);
+        // the configured value is sliced to the unconnected bits
+        assert!(th.contains("wire [7:0] irq;"));
+        assert!(th.contains("assign irq[3:0] = irq_cont_0_if.irq;"));
+        assert!(th.contains("assign irq[7:4] = 4'b1010;"));
+        assert!(th.contains(".mask (4'b0011)"));
+    }
+
+    #[test]
+    fn harness_dut_clocks() {
+        let vip = r#"
+            name = "afifo"
+            clocks = [{ name = "wclk" }, { name = "rclk", period = 30 }]
+            resets = [{ name = "wrst_n" }, { name = "rrst", clock = "rclk", polarity = "high" }]
+            ports = ["wdata [7:0]", { name = "rdata", dimensions = [[7, 0]], clock = "rclk" }]
+        "#;
+        let instances = r#"
+            [[instances]]
+            vip_name = "afifo"
+            connect = { wdata = "wdata", rdata = "rdata" }
+        "#;
+        let project = r#"
+            [dut]
+            clock = "wclk"
+            reset = "wrst_n"
+        "#;
+        let dut = get_dut(&[
+            ("wclk", PortDirection::INPUT),
+            ("wrst_n", PortDirection::INPUT),
+            ("rclk", PortDirection::INPUT),
+            ("rrst", PortDirection::INPUT),
+            ("wdata [7:0]", PortDirection::INPUT),
+            ("rdata [7:0]", PortDirection::OUTPUT),
+        ]);
+
+        let th = render_tb("top/tb/th.sv.j2", &[vip], instances, project, &dut);
+        // DUT inputs named like a VIP clock or reset follow its harness signal
+        assert!(th.contains(".wclk (clk)"));
+        assert!(th.contains(".wrst_n (rst)"));
+        assert!(th.contains(".rclk (clk_30)"));
+        assert!(th.contains(".rrst (~rst_30)"));
+        assert!(!th.contains("('0)"));
+    }
+
+    #[test]
+    fn item_class_handles() {
+        let vip = r#"
+            name = "bus"
+            ports = ["data [7:0]"]
+            [item]
+            members = ["bit [7:0] data", "my_class obj", "my_class objs[4]", "my_class q[$]"]
+        "#;
+
+        let tx = render_vip("vip/tx.sv.j2", vip);
+        // handles are cloned, not shared with the copied item
+        assert!(tx.contains("  data = rhs_.data;\n  obj = rhs_.obj;\n  if (rhs_.obj != null)\n    $cast(obj, rhs_.obj.clone());"));
+        assert!(tx.contains("  foreach (objs[i0])\n    if (rhs_.objs[i0] != null)\n      $cast(objs[i0], rhs_.objs[i0].clone());"));
+        assert!(tx.contains("  foreach (q[i0])\n    if (rhs_.q[i0] != null)\n      $cast(q[i0], rhs_.q[i0].clone());"));
+        assert!(!tx.contains("$cast(data"));
+        // null handles are created before being unpacked
+        assert!(tx.contains("  if (obj == null)\n    obj = my_class::type_id::create(\"obj\");\n  packer.unpack_object(obj);"));
+        assert!(tx.contains("objs[i0] = my_class::type_id::create($sformatf(\"objs[%0d]\", i0));"));
+        assert!(tx.contains("element = my_class::type_id::create(\"element\");"));
+    }
+
+    #[test]
+    fn item_recording() {
+        let vip = r#"
+            name = "bus"
+            ports = ["data [7:0]"]
+            [item]
+            members = ["bit [7:0] data", "string tag", "real ratio", "byte payload[]", "int q[$]", "my_class obj"]
+        "#;
+
+        let tx = render_vip("vip/tx.sv.j2", vip);
+        // fields are recorded according to their type, arrays element by element
+        assert!(tx.contains("`uvm_record_field(\"data\", data)"));
+        assert!(tx.contains("`uvm_record_string(\"tag\", tag)"));
+        assert!(tx.contains("`uvm_record_real(\"ratio\", ratio)"));
+        assert!(tx.contains("  foreach (payload[i0])\n    `uvm_record_field($sformatf(\"payload[%0d]\", i0), payload[i0])"));
+        assert!(tx.contains(
+            "  foreach (q[i0])\n    `uvm_record_field($sformatf(\"q[%0d]\", i0), q[i0])"
+        ));
+        assert!(tx.contains("recorder.record_object(\"obj\", obj);"));
+        assert!(!tx.contains("`uvm_record_field(\"payload\""));
+    }
+
+    #[test]
+    fn vip_resets() {
+        let vip = r#"
+            name = "bus"
+            ports = [{ name = "data", direction = "out" }, { name = "ack", direction = "in" }]
+            clock = "clk"
+            resets = [{ name = "rst_n" }, { name = "srst", polarity = "high" }]
+        "#;
+
+        // the driver and monitor are in reset while any reset is asserted
+        let driver = render_vip("vip/driver.sv.j2", vip);
+        assert!(driver.contains("wait (vif.rst_n === 1'b1 && vif.srst === 1'b0);"));
+        assert!(driver.contains("@(negedge vif.rst_n or posedge vif.srst);"));
+        assert!(driver.contains("vif.cb_cont_drv.data <= '0;"));
+        assert!(driver.contains("vif.cb_resp_drv.ack <= '0;"));
+        // only the interrupted item is completed before stopping the sequences
+        assert!(driver.contains("    if (m_item_pending)\n    begin\n      seq_item_port.item_done();\n      m_item_pending = 0;\n    end\n    m_sequencer.stop_sequences();"));
+        assert!(!driver.contains("has_do_available"));
+        let monitor = render_vip("vip/monitor.sv.j2", vip);
+        assert!(monitor.contains("wait (vif.rst_n === 1'b1 && vif.srst === 1'b0);"));
+        assert!(monitor.contains("@(negedge vif.rst_n or posedge vif.srst);"));
+        let agent = render_vip("vip/agent.sv.j2", vip);
+        assert!(agent.contains("m_driver.m_sequencer = m_sequencer;"));
+
+        // without clocking blocks, idle values go through the interface drivers
+        let vip = format!("{}\nuse_clock_block = false", vip);
+        let driver = render_vip("vip/driver.sv.j2", &vip);
+        assert!(driver.contains("vif.data_drv = '0;"));
+        assert!(driver.contains("vif.ack_drv = '0;"));
+        assert!(!driver.contains("TODO: drive idle values"));
+        let interface = render_vip("vip/if.sv.j2", &vip);
+        assert!(
+            interface.contains("logic [$bits(data)-1:0] data_drv = 'z;\n  assign data = data_drv;")
+        );
+    }
+
+    #[test]
+    fn serial_word_sizes() {
+        // knobs are checked against the width of the generated or declared item members
+        let vip = r#"
+            name = "bus"
+            protocol = "uart"
+            ports = ["tx", "rx"]
+            item = { members = ["rand bit [15:0] data"] }
+        "#;
+        let agent = render_vip("vip/agent.sv.j2", vip);
+        assert!(agent.contains("  begin\n    bus_tx tx;\n    if (m_config.data_bits > $bits(tx.data))\n      `uvm_fatal("));
+        let tx = render_vip("vip/tx.sv.j2", vip);
+        assert!(tx.contains("rand bit [15:0] data;"));
+
+        let vip = r#"
+            name = "bus"
+            protocol = "spi"
+            ports = ["sclk", "cs_n", "mosi", "miso"]
+        "#;
+        let agent = render_vip("vip/agent.sv.j2", vip);
+        assert!(agent.contains(
+            "if (m_config.word_size > $bits(tx.mosi) || m_config.word_size > $bits(tx.miso))"
+        ));
+    }
+
+    #[test]
+    fn responder_requests() {
+        let protocols = [
+            (
+                r#"protocol = "valid_ready"
+                ports = ["data [7:0]", "valid", "ready"]
+                item = { members = ["bit [7:0] data"] }"#,
+                "if (vif.cb_mon.valid === 1'b1 && !is_requested)",
+            ),
+            (
+                r#"protocol = "axi4_stream"
+                ports = ["tdata [7:0]", "tvalid", "tready"]
+                item = { members = ["bit [7:0] tdata"] }"#,
+                "if (vif.cb_mon.tvalid === 1'b1 && !is_requested)",
+            ),
+            (
+                r#"protocol = "apb"
+                ports = ["paddr [7:0]", "psel", "penable", "pwrite", "pwdata [7:0]", "prdata [7:0]", "pready"]
+                item = { members = ["bit [7:0] addr", "bit write", "bit [7:0] data"] }"#,
+                "if (vif.cb_mon.psel === 1'b1 && vif.cb_mon.penable !== 1'b1)",
+            ),
+            (
+                r#"protocol = "axi4_lite"
+                ports = [
+                    "awaddr [7:0]", "awvalid", "awready", "wdata [31:0]", "wvalid", "wready",
+                    "bresp [1:0]", "bvalid", "bready", "araddr [7:0]", "arvalid", "arready",
+                    "rdata [31:0]", "rresp [1:0]", "rvalid", "rready",
+                ]"#,
+                "if (!is_read_requested && vif.cb_mon.arvalid === 1'b1)",
+            ),
+            (
+                r#"protocol = "uart"
+                ports = ["tx", "rx"]"#,
+                "// controller frames are the requests answered by the responder",
+            ),
+            (
+                r#"protocol = "spi"
+                ports = ["sclk", "cs_n", "mosi", "miso"]"#,
+                "// the sampled word is the request answered by the responder on the next transfer",
+            ),
+            (
+                r#"ports = [{ name = "data", dimensions = [[7, 0]], member = "data", qualifier = "vld" }, "vld out"]
+                item = { members = ["bit [7:0] data"] }"#,
+                "if (vif.cb_mon.vld === 1'b1)\n      request_port.write(m_trans);",
+            ),
+        ];
+        for (protocol, request) in protocols {
+            let vip = format!("name = \"bus\"\nclock = \"clk\"\n{}", protocol);
+            // requests are published before the responder answers them
+            let monitor = render_vip("vip/monitor.sv.j2", &vip);
+            assert!(monitor.contains(request), "{}", protocol);
+            assert!(monitor.contains("request_port.write("), "{}", protocol);
+        }
+
+        let vip = r#"
+            name = "bus"
+            ports = ["data [7:0]", "valid", "ready"]
+            clock = "clk"
+            protocol = "valid_ready"
+            item = { members = ["bit [7:0] data"] }
+        "#;
+        let agent = render_vip("vip/agent.sv.j2", vip);
+        assert!(agent.contains("if (!m_config.is_controller && m_config.is_reactive)\n      m_monitor.request_port.connect(m_sequencer.request_fifo.analysis_export);"));
+
+        // responder instances are reactive, the responder sequence replaces their default sequence
+        let instances = r#"
+            [[instances]]
+            vip_name = "bus"
+            connected_to = ["data_in", "data_in_vld", "data_in_rdy"]
+            mode = "Responder"
+        "#;
+        let dut = get_dut(&[
+            ("data_in [7:0]", PortDirection::INPUT),
+            ("data_in_vld", PortDirection::INPUT),
+            ("data_in_rdy", PortDirection::OUTPUT),
+        ]);
+        let config = render_tb("top/config.sv.j2", &[vip], instances, "", &dut);
+        assert!(config.contains("m_bus_resp_0_config.is_reactive = 1;"));
+        let seq_lib = render_tb("top/seq_lib.sv.j2", &[vip], instances, "", &dut);
+        assert!(seq_lib.contains("if (m_bus_resp_0_agent.m_config.is_active == UVM_ACTIVE && m_bus_resp_0_agent.m_config.is_reactive)"));
+        assert!(seq_lib.contains(
+            "    fork\n      seq.start(m_bus_resp_0_agent.m_sequencer, this);\n    join_none"
+        ));
+        assert!(seq_lib.contains("if (m_bus_resp_0_agent.m_config.is_active == UVM_ACTIVE && !m_bus_resp_0_agent.m_config.is_reactive)"));
+    }
+}
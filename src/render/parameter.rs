@@ -0,0 +1,268 @@
+use std::collections::{BTreeMap, HashMap};
+use std::iter::Peekable;
+use std::str::Chars;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::config::vip::Parameter as Parametercfg;
+use crate::dut::utils::{fmt_suggestion, get_closest_name};
+use crate::render::member::is_identifier;
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct Parameter {
+    pub name: String,
+    pub default: u32,
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ParseParameterError {
+    #[error("invalid parameter name {0}")]
+    InvalidName(String),
+
+    #[error("parameter {0} declared several times")]
+    DuplicatedName(String),
+
+    #[error("unknown parameter {name} in {expr}{}", fmt_suggestion(.suggestion))]
+    UnknownParameter {
+        name: String,
+        expr: String,
+        suggestion: Option<String>,
+    },
+
+    #[error("invalid expression {0} (expected a positive integer expression of parameters)")]
+    InvalidExpression(String),
+}
+
+pub fn get_parameters(parameters: &[Parametercfg]) -> Result<Vec<Parameter>, ParseParameterError> {
+    let mut render_parameters: Vec<Parameter> = Vec::new();
+    for p in parameters {
+        if !is_identifier(&p.name) {
+            return Err(ParseParameterError::InvalidName(p.name.clone()));
+        }
+        if render_parameters.iter().any(|r| r.name == p.name) {
+            return Err(ParseParameterError::DuplicatedName(p.name.clone()));
+        }
+        render_parameters.push(Parameter {
+            name: p.name.clone(),
+            default: p.default,
+        });
+    }
+    Ok(render_parameters)
+}
+
+// defaults overridden by the values given in an instance, unknown ones being ignored
+pub fn get_values(
+    parameters: &[Parameter],
+    overrides: &BTreeMap<String, u32>,
+) -> HashMap<String, u32> {
+    parameters
+        .iter()
+        .map(|p| {
+            let value = overrides.get(&p.name).copied().unwrap_or(p.default);
+            (p.name.clone(), value)
+        })
+        .collect()
+}
+
+// class and interface parameter ports, e.g. ' #(int DATA_W = 16)'
+pub fn get_parameter_ports(parameters: &[Parameter]) -> String {
+    if parameters.is_empty() {
+        return "".to_string();
+    }
+    let ports: Vec<String> = parameters
+        .iter()
+        .map(|p| format!("int {} = {}", p.name, p.default))
+        .collect();
+    format!(" #({})", ports.join(", "))
+}
+
+// named parameter assignments, e.g. ' #(.DATA_W(32))'
+pub fn get_specialization<'a>(assignments: impl Iterator<Item = (&'a String, String)>) -> String {
+    let assignments: Vec<String> = assignments
+        .map(|(name, value)| format!(".{}({})", name, value))
+        .collect();
+    if assignments.is_empty() {
+        "".to_string()
+    } else {
+        format!(" #({})", assignments.join(", "))
+    }
+}
+
+// integer arithmetic (+, -, *, /, %, $clog2) on literals and parameters
+pub fn evaluate(expr: &str, values: &HashMap<String, u32>) -> Result<u32, ParseParameterError> {
+    let mut evaluator = Evaluator {
+        expr,
+        values,
+        chars: expr.chars().peekable(),
+    };
+    let value = evaluator.sum()?;
+    evaluator.skip_whitespaces();
+    if evaluator.chars.peek().is_some() {
+        return Err(evaluator.invalid());
+    }
+    u32::try_from(value).map_err(|_| evaluator.invalid())
+}
+
+struct Evaluator<'a> {
+    expr: &'a str,
+    values: &'a HashMap<String, u32>,
+    chars: Peekable<Chars<'a>>,
+}
+
+impl Evaluator<'_> {
+    fn invalid(&self) -> ParseParameterError {
+        ParseParameterError::InvalidExpression(self.expr.to_string())
+    }
+
+    fn skip_whitespaces(&mut self) {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+    }
+
+    fn next_operator(&mut self, operators: &[char]) -> Option<char> {
+        self.skip_whitespaces();
+        self.chars.next_if(|c| operators.contains(c))
+    }
+
+    fn sum(&mut self) -> Result<i64, ParseParameterError> {
+        let mut value = self.product()?;
+        while let Some(op) = self.next_operator(&['+', '-']) {
+            let rhs = self.product()?;
+            value = match op {
+                '+' => value.checked_add(rhs),
+                _ => value.checked_sub(rhs),
+            }
+            .ok_or_else(|| self.invalid())?;
+        }
+        Ok(value)
+    }
+
+    fn product(&mut self) -> Result<i64, ParseParameterError> {
+        let mut value = self.factor()?;
+        while let Some(op) = self.next_operator(&['*', '/', '%']) {
+            let rhs = self.factor()?;
+            value = match op {
+                '*' => value.checked_mul(rhs),
+                '/' => value.checked_div(rhs),
+                _ => value.checked_rem(rhs),
+            }
+            .ok_or_else(|| self.invalid())?;
+        }
+        Ok(value)
+    }
+
+    fn factor(&mut self) -> Result<i64, ParseParameterError> {
+        self.skip_whitespaces();
+        match self.chars.peek() {
+            Some('(') => self.parenthesized(),
+            Some('-') => {
+                self.chars.next();
+                Ok(-self.factor()?)
+            }
+            Some(c) if c.is_ascii_digit() => {
+                let mut digits = String::new();
+                while let Some(c) = self.chars.next_if(|c| c.is_ascii_digit() || *c == '_') {
+                    digits.push(c);
+                }
+                digits.replace('_', "").parse().map_err(|_| self.invalid())
+            }
+            Some(c) if c.is_ascii_alphabetic() || *c == '_' || *c == '$' => {
+                let mut name = String::new();
+                while let Some(c) = self
+                    .chars
+                    .next_if(|c| c.is_ascii_alphanumeric() || *c == '_' || *c == '$')
+                {
+                    name.push(c);
+                }
+                if name == "$clog2" {
+                    let value = self.parenthesized()?;
+                    return match value {
+                        ..0 => Err(self.invalid()),
+                        0 => Ok(0),
+                        _ => Ok(i64::from(64 - (value - 1).leading_zeros())),
+                    };
+                }
+                match self.values.get(&name) {
+                    Some(value) => Ok(i64::from(*value)),
+                    None => {
+                        let names: Vec<String> = self.values.keys().cloned().collect();
+                        Err(ParseParameterError::UnknownParameter {
+                            suggestion: get_closest_name(&name, &names),
+                            name,
+                            expr: self.expr.to_string(),
+                        })
+                    }
+                }
+            }
+            _ => Err(self.invalid()),
+        }
+    }
+
+    fn parenthesized(&mut self) -> Result<i64, ParseParameterError> {
+        if self.next_operator(&['(']).is_none() {
+            return Err(self.invalid());
+        }
+        let value = self.sum()?;
+        match self.next_operator(&[')']) {
+            Some(_) => Ok(value),
+            None => Err(self.invalid()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+
+    use super::{evaluate, get_parameter_ports, get_specialization, get_values, Parameter};
+
+    #[test]
+    fn parameter_expressions() {
+        let parameters = vec![
+            Parameter {
+                name: "DATA_W".to_string(),
+                default: 16,
+            },
+            Parameter {
+                name: "DEPTH".to_string(),
+                default: 8,
+            },
+        ];
+        assert_eq!(
+            get_parameter_ports(&parameters),
+            " #(int DATA_W = 16, int DEPTH = 8)"
+        );
+
+        let overrides = BTreeMap::from([("DATA_W".to_string(), 32)]);
+        let values = get_values(&parameters, &overrides);
+        assert_eq!(
+            get_specialization(overrides.iter().map(|(n, v)| (n, v.to_string()))),
+            " #(.DATA_W(32))"
+        );
+
+        let valid = [
+            ("DATA_W-1", 31),
+            ("DATA_W / 8 - 1", 3),
+            ("(DEPTH + 1) * 2", 18),
+            ("$clog2(DEPTH)", 3),
+            ("$clog2(DEPTH + 1) - 1", 3),
+            ("1_000 % 7", 6),
+        ];
+        for (expr, value) in valid {
+            assert_eq!(evaluate(expr, &values), Ok(value), "{}", expr);
+        }
+
+        let invalid = [
+            "DATA_WW-1",
+            "DEPTH - 9",
+            "DATA_W / 0",
+            "(DEPTH",
+            "DEPTH DEPTH",
+            "",
+        ];
+        for expr in invalid {
+            assert!(evaluate(expr, &values).is_err(), "{}", expr);
+        }
+        assert!(evaluate("N", &HashMap::new()).is_err());
+    }
+}
@@ -0,0 +1,719 @@
+use std::collections::HashMap;
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::config::vip::{ProtocolKind, Role};
+use crate::dut::utils::{fmt_suggestion, get_closest_name, PortDirection};
+use crate::render::member::{Member, TypeCategory, TypeScope};
+use crate::render::vip::VIPPort;
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct Protocol {
+    pub kind: ProtocolKind,
+    // driven through clocking blocks, serial protocols drive ports directly
+    pub is_clocked: bool,
+    // control signals, role -> port
+    pub signals: HashMap<String, String>,
+    // ports carrying item members, role -> ports
+    pub fields: HashMap<String, Vec<ProtocolField>>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct ProtocolField {
+    pub port: String,
+    pub member: String,
+    pub enum_kind: Option<String>,
+    pub width: u32,
+}
+
+#[derive(Debug, Error)]
+pub enum ParseProtocolError {
+    #[error("{0} protocol requires a VIP clock")]
+    MissingClock(ProtocolKind),
+
+    #[error("{0} protocol requires clocking blocks (use_clock_block = true)")]
+    MissingClockBlock(ProtocolKind),
+
+    #[error("unknown {protocol} role {role}{}", fmt_suggestion(.suggestion))]
+    UnknownRole {
+        protocol: ProtocolKind,
+        role: String,
+        suggestion: Option<String>,
+    },
+
+    #[error("missing {protocol} role {role} (no port found, set it in [roles])")]
+    MissingRole {
+        protocol: ProtocolKind,
+        role: String,
+    },
+
+    #[error("{protocol} role {role} expects a single port")]
+    MultiplePorts {
+        protocol: ProtocolKind,
+        role: String,
+    },
+
+    #[error("unknown port {port} for role {role}{}", fmt_suggestion(.suggestion))]
+    UnknownPort {
+        role: String,
+        port: String,
+        suggestion: Option<String>,
+    },
+
+    #[error("port {0} used by several roles")]
+    DuplicatedPort(String),
+
+    #[error("port {port} of role {role} must be 1 bit wide")]
+    InvalidWidth { role: String, port: String },
+
+    #[error(
+        "port {port} of role {role} declared {found} but expected {expected} (controller side)"
+    )]
+    InvalidDirection {
+        role: String,
+        port: String,
+        expected: PortDirection,
+        found: PortDirection,
+    },
+
+    #[error("no item member {member} (or {port}) for port {port} of role {role}{}", fmt_suggestion(.suggestion))]
+    UnknownMember {
+        role: String,
+        port: String,
+        member: String,
+        suggestion: Option<String>,
+    },
+
+    #[error("item member {0} mapped to a port must be of integral or enum type")]
+    InvalidMember(String),
+}
+
+impl std::fmt::Display for ProtocolKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ProtocolKind::ValidReady => write!(f, "valid_ready"),
+            ProtocolKind::Apb => write!(f, "apb"),
+            ProtocolKind::Axi4Lite => write!(f, "axi4_lite"),
+            ProtocolKind::Axi4Stream => write!(f, "axi4_stream"),
+            ProtocolKind::Uart => write!(f, "uart"),
+            ProtocolKind::Spi => write!(f, "spi"),
+        }
+    }
+}
+
+enum RoleKind {
+    // 1 bit control signal
+    Signal,
+    // ports mapped to item members
+    Field,
+}
+
+struct RoleSpec {
+    name: &'static str,
+    kind: RoleKind,
+    // controller side
+    direction: PortDirection,
+    // default port name suffixes, remaining ports when empty
+    suffixes: Vec<&'static str>,
+    // item member used when none is named after the port
+    member: Option<&'static str>,
+    is_optional: bool,
+    is_multiple: bool,
+    // member declared from the port width when missing
+    is_generated: bool,
+}
+
+impl RoleSpec {
+    fn signal(name: &'static str, direction: PortDirection) -> Self {
+        RoleSpec {
+            name,
+            kind: RoleKind::Signal,
+            direction,
+            suffixes: Vec::new(),
+            member: None,
+            is_optional: false,
+            is_multiple: false,
+            is_generated: false,
+        }
+    }
+
+    fn field(name: &'static str, direction: PortDirection, member: &'static str) -> Self {
+        RoleSpec {
+            name,
+            kind: RoleKind::Field,
+            direction,
+            suffixes: Vec::new(),
+            member: Some(member),
+            is_optional: false,
+            is_multiple: false,
+            is_generated: false,
+        }
+    }
+
+    // port named or suffixed by the role name
+    fn named(mut self) -> Self {
+        self.suffixes = vec![self.name];
+        self
+    }
+
+    fn optional(mut self) -> Self {
+        self.is_optional = true;
+        self
+    }
+
+    fn generated(mut self) -> Self {
+        self.is_generated = matches!(self.kind, RoleKind::Field);
+        self
+    }
+}
+
+fn get_role_specs(kind: &ProtocolKind) -> Vec<RoleSpec> {
+    match kind {
+        ProtocolKind::ValidReady => vec![
+            RoleSpec {
+                suffixes: vec!["valid", "vld"],
+                ..RoleSpec::signal("valid", PortDirection::OUTPUT)
+            },
+            RoleSpec {
+                suffixes: vec!["ready", "rdy"],
+                ..RoleSpec::signal("ready", PortDirection::INPUT)
+            },
+            RoleSpec {
+                kind: RoleKind::Field,
+                member: None,
+                is_multiple: true,
+                ..RoleSpec::signal("payload", PortDirection::OUTPUT)
+            },
+        ],
+        ProtocolKind::Apb => vec![
+            RoleSpec::field("paddr", PortDirection::OUTPUT, "addr").named(),
+            RoleSpec::signal("psel", PortDirection::OUTPUT).named(),
+            RoleSpec::signal("penable", PortDirection::OUTPUT).named(),
+            RoleSpec::field("pwrite", PortDirection::OUTPUT, "write").named(),
+            RoleSpec::field("pwdata", PortDirection::OUTPUT, "data").named(),
+            RoleSpec::field("prdata", PortDirection::INPUT, "data").named(),
+            RoleSpec::signal("pready", PortDirection::INPUT).named(),
+            RoleSpec::field("pslverr", PortDirection::INPUT, "slverr")
+                .named()
+                .optional(),
+        ],
+        ProtocolKind::Axi4Lite => [
+            RoleSpec::signal("awvalid", PortDirection::OUTPUT),
+            RoleSpec::signal("awready", PortDirection::INPUT),
+            RoleSpec::field("awaddr", PortDirection::OUTPUT, "addr"),
+            RoleSpec::field("awprot", PortDirection::OUTPUT, "prot").optional(),
+            RoleSpec::signal("wvalid", PortDirection::OUTPUT),
+            RoleSpec::signal("wready", PortDirection::INPUT),
+            RoleSpec::field("wdata", PortDirection::OUTPUT, "data"),
+            RoleSpec::field("wstrb", PortDirection::OUTPUT, "strb").optional(),
+            RoleSpec::signal("bvalid", PortDirection::INPUT),
+            RoleSpec::signal("bready", PortDirection::OUTPUT),
+            RoleSpec::field("bresp", PortDirection::INPUT, "resp"),
+            RoleSpec::signal("arvalid", PortDirection::OUTPUT),
+            RoleSpec::signal("arready", PortDirection::INPUT),
+            RoleSpec::field("araddr", PortDirection::OUTPUT, "addr"),
+            RoleSpec::field("arprot", PortDirection::OUTPUT, "prot").optional(),
+            RoleSpec::signal("rvalid", PortDirection::INPUT),
+            RoleSpec::signal("rready", PortDirection::OUTPUT),
+            RoleSpec::field("rdata", PortDirection::INPUT, "data"),
+            RoleSpec::field("rresp", PortDirection::INPUT, "resp"),
+        ]
+        .into_iter()
+        .map(|s| s.named().generated())
+        .collect(),
+        ProtocolKind::Axi4Stream => [
+            RoleSpec::signal("tvalid", PortDirection::OUTPUT),
+            RoleSpec::signal("tready", PortDirection::INPUT),
+            RoleSpec::field("tdata", PortDirection::OUTPUT, "data"),
+            RoleSpec::field("tstrb", PortDirection::OUTPUT, "strb").optional(),
+            RoleSpec::field("tkeep", PortDirection::OUTPUT, "keep").optional(),
+            RoleSpec::field("tlast", PortDirection::OUTPUT, "last").optional(),
+            RoleSpec::field("tid", PortDirection::OUTPUT, "id").optional(),
+            RoleSpec::field("tdest", PortDirection::OUTPUT, "dest").optional(),
+            RoleSpec::field("tuser", PortDirection::OUTPUT, "user").optional(),
+        ]
+        .into_iter()
+        .map(|s| s.named().generated())
+        .collect(),
+        ProtocolKind::Uart => vec![
+            RoleSpec {
+                suffixes: vec!["tx", "txd"],
+                ..RoleSpec::signal("tx", PortDirection::OUTPUT)
+            },
+            RoleSpec {
+                suffixes: vec!["rx", "rxd"],
+                ..RoleSpec::signal("rx", PortDirection::INPUT).optional()
+            },
+        ],
+        ProtocolKind::Spi => vec![
+            RoleSpec {
+                suffixes: vec!["sclk", "sck"],
+                ..RoleSpec::signal("sclk", PortDirection::OUTPUT)
+            },
+            RoleSpec {
+                suffixes: vec!["cs", "csn", "cs_n", "ss", "ssn", "ss_n"],
+                ..RoleSpec::signal("cs", PortDirection::OUTPUT)
+            },
+            RoleSpec {
+                suffixes: vec!["mosi", "copi"],
+                ..RoleSpec::signal("mosi", PortDirection::OUTPUT)
+            },
+            RoleSpec {
+                suffixes: vec!["miso", "cipo"],
+                ..RoleSpec::signal("miso", PortDirection::INPUT).optional()
+            },
+        ],
+    }
+}
+
+fn is_clocked(kind: &ProtocolKind) -> bool {
+    !matches!(kind, ProtocolKind::Uart | ProtocolKind::Spi)
+}
+
+// item members needed by the protocol beyond the ports ones
+fn get_protocol_members(kind: &ProtocolKind) -> Vec<&'static str> {
+    match kind {
+        ProtocolKind::Axi4Lite => vec!["rand bit write"],
+        ProtocolKind::Uart => vec![
+            "rand bit [7:0] data",
+            "bit is_rx",
+            "bit parity_error",
+            "bit framing_error",
+        ],
+        ProtocolKind::Spi => vec!["rand bit [7:0] mosi", "rand bit [7:0] miso"],
+        _ => Vec::new(),
+    }
+}
+
+pub struct ProtocolScope<'a> {
+    pub ports: &'a mut [VIPPort],
+    pub members: &'a mut Vec<Member>,
+    pub types: &'a TypeScope<'a>,
+    pub has_clock: bool,
+    pub use_clock_block: bool,
+}
+
+pub fn get_protocol(
+    kind: &ProtocolKind,
+    roles: &HashMap<String, Role>,
+    mut scope: ProtocolScope,
+) -> Result<Protocol, ParseProtocolError> {
+    if is_clocked(kind) && !scope.has_clock {
+        return Err(ParseProtocolError::MissingClock(kind.clone()));
+    }
+    if is_clocked(kind) && !scope.use_clock_block {
+        return Err(ParseProtocolError::MissingClockBlock(kind.clone()));
+    }
+
+    let specs = get_role_specs(kind);
+    let specs_names: Vec<String> = specs.iter().map(|s| s.name.to_string()).collect();
+    for role in roles.keys() {
+        if !specs_names.contains(role) {
+            return Err(ParseProtocolError::UnknownRole {
+                protocol: kind.clone(),
+                role: role.clone(),
+                suggestion: get_closest_name(role, &specs_names),
+            });
+        }
+    }
+
+    let ports_names: Vec<String> = scope.ports.iter().map(|p| p.name.clone()).collect();
+    let mut assignments: Vec<(&RoleSpec, Vec<String>)> = Vec::new();
+    // explicit roles first, defaults are resolved among the remaining ports
+    for spec in specs.iter().filter(|s| roles.contains_key(s.name)) {
+        let ports = match &roles[spec.name] {
+            Role::Port(port) => vec![port.clone()],
+            Role::Ports(ports) => ports.clone(),
+        };
+        for port in &ports {
+            if !ports_names.contains(port) {
+                return Err(ParseProtocolError::UnknownPort {
+                    role: spec.name.to_string(),
+                    port: port.clone(),
+                    suggestion: get_closest_name(port, &ports_names),
+                });
+            }
+        }
+        assignments.push((spec, ports));
+    }
+    for spec in specs.iter().filter(|s| !roles.contains_key(s.name)) {
+        let assigned: Vec<&String> = assignments.iter().flat_map(|(_, p)| p).collect();
+        let remaining = ports_names.iter().filter(|p| !assigned.contains(p));
+        let ports: Vec<String> = if spec.suffixes.is_empty() {
+            remaining.cloned().collect()
+        } else {
+            remaining
+                .filter(|p| {
+                    spec.suffixes
+                        .iter()
+                        .any(|s| *p == s || p.ends_with(&format!("_{}", s)))
+                })
+                .cloned()
+                .collect()
+        };
+        assignments.push((spec, ports));
+    }
+
+    for declaration in get_protocol_members(kind) {
+        let member: Member = declaration.parse().unwrap();
+        match scope.members.iter().find(|m| m.name == member.name) {
+            Some(m) => {
+                let (category, _) = scope.types.get_category(m);
+                if !matches!(category, TypeCategory::Integral | TypeCategory::Enum) {
+                    return Err(ParseProtocolError::InvalidMember(m.name.clone()));
+                }
+            }
+            None => add_member(&mut scope, member),
+        }
+    }
+
+    let mut signals = HashMap::new();
+    let mut fields = HashMap::new();
+    let mut used: Vec<String> = Vec::new();
+    for (spec, ports) in assignments {
+        let role = spec.name.to_string();
+        match ports.len() {
+            0 if spec.is_optional => continue,
+            0 => {
+                return Err(ParseProtocolError::MissingRole {
+                    protocol: kind.clone(),
+                    role,
+                })
+            }
+            1 => (),
+            _ if !spec.is_multiple => {
+                return Err(ParseProtocolError::MultiplePorts {
+                    protocol: kind.clone(),
+                    role,
+                })
+            }
+            _ => (),
+        }
+        for port in &ports {
+            if used.contains(port) {
+                return Err(ParseProtocolError::DuplicatedPort(port.clone()));
+            }
+            used.push(port.clone());
+            set_direction(scope.ports, &role, port, &spec.direction)?;
+        }
+
+        match spec.kind {
+            RoleKind::Signal => {
+                if get_port_width(scope.ports, &ports[0]) != 1 {
+                    return Err(ParseProtocolError::InvalidWidth {
+                        role,
+                        port: ports[0].clone(),
+                    });
+                }
+                signals.insert(role, ports[0].clone());
+            }
+            RoleKind::Field => {
+                let mut role_fields = Vec::new();
+                for port in ports {
+                    role_fields.push(get_field(&mut scope, spec, &port)?);
+                }
+                fields.insert(role, role_fields);
+            }
+        }
+    }
+
+    for p in ports_names.iter().filter(|p| !used.contains(p)) {
+        warn!("port {} not driven by {} protocol", p, kind);
+    }
+
+    Ok(Protocol {
+        kind: kind.clone(),
+        is_clocked: is_clocked(kind),
+        signals,
+        fields,
+    })
+}
+
+fn set_direction(
+    ports: &mut [VIPPort],
+    role: &str,
+    port: &str,
+    direction: &PortDirection,
+) -> Result<(), ParseProtocolError> {
+    let p = ports.iter_mut().find(|p| p.name == port).unwrap();
+    if p.is_direction_set && p.properties.direction != *direction {
+        return Err(ParseProtocolError::InvalidDirection {
+            role: role.to_string(),
+            port: port.to_string(),
+            expected: direction.clone(),
+            found: p.properties.direction.clone(),
+        });
+    }
+    p.properties.direction = direction.clone();
+    p.is_direction_set = true;
+    Ok(())
+}
+
+fn get_port_width(ports: &[VIPPort], port: &str) -> u32 {
+    let p = ports.iter().find(|p| p.name == port).unwrap();
+    p.properties
+        .dimensions
+        .iter()
+        .map(|(end, start)| end.abs_diff(*start) + 1)
+        .product()
+}
+
+fn add_member(scope: &mut ProtocolScope, mut member: Member) {
+    member.fields = scope.types.get_fields(&member);
+    scope.members.push(member);
+}
+
+fn get_field(
+    scope: &mut ProtocolScope,
+    spec: &RoleSpec,
+    port: &str,
+) -> Result<ProtocolField, ParseProtocolError> {
+    let default_member = spec.member.unwrap_or(port);
+    let is_declared = |name: &str| scope.members.iter().any(|m| m.name == name);
+    if spec.is_generated && !is_declared(port) && !is_declared(default_member) {
+        let width = get_port_width(scope.ports, port);
+        let declaration = format!(
+            "{}bit {}{}",
+            if spec.direction == PortDirection::OUTPUT {
+                "rand "
+            } else {
+                ""
+            },
+            if width > 1 {
+                format!("[{}:0] ", width - 1)
+            } else {
+                "".to_string()
+            },
+            default_member
+        );
+        add_member(scope, declaration.parse().unwrap());
+    }
+
+    let member = scope
+        .members
+        .iter()
+        .find(|m| m.name == port)
+        .or_else(|| scope.members.iter().find(|m| m.name == default_member));
+    let member = match member {
+        Some(member) => member,
+        None => {
+            let members_names: Vec<String> = scope.members.iter().map(|m| m.name.clone()).collect();
+            return Err(ParseProtocolError::UnknownMember {
+                role: spec.name.to_string(),
+                port: port.to_string(),
+                member: default_member.to_string(),
+                suggestion: get_closest_name(default_member, &members_names),
+            });
+        }
+    };
+
+    let (category, enum_kind) = scope.types.get_category(member);
+    if !matches!(category, TypeCategory::Integral | TypeCategory::Enum) {
+        return Err(ParseProtocolError::InvalidMember(member.name.clone()));
+    }
+    let port_width = get_port_width(scope.ports, port);
+    match scope.types.get_width(member) {
+        Some(width) if width != port_width => warn!(
+            "port {} ({} bits) and item member {} ({} bits) widths differ",
+            port, port_width, member.name, width
+        ),
+        _ => (),
+    }
+
+    Ok(ProtocolField {
+        port: port.to_string(),
+        member: member.name.clone(),
+        enum_kind,
+        width: port_width,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{get_protocol, ParseProtocolError, Protocol, ProtocolScope};
+    use crate::config::vip::{ProtocolKind, Role};
+    use crate::dut::utils::PortDirection;
+    use crate::render::member::{Member, TypeScope};
+    use crate::render::vip::VIPPort;
+
+    const MEMBERS: [&str; 6] = [
+        "rand bit [15:0] data",
+        "int rate",
+        "string tag",
+        "rand bit [11:0] addr",
+        "rand bit write",
+        "bit slverr",
+    ];
+
+    fn protocol(
+        kind: ProtocolKind,
+        ports: &[&str],
+        roles: Vec<(&str, Role)>,
+        has_clock: bool,
+    ) -> Result<(Protocol, Vec<VIPPort>, Vec<Member>), ParseProtocolError> {
+        let mut ports: Vec<VIPPort> = ports.iter().map(|p| p.parse().unwrap()).collect();
+        let mut members: Vec<Member> = MEMBERS.iter().map(|m| m.parse().unwrap()).collect();
+        let roles: HashMap<String, Role> =
+            roles.into_iter().map(|(r, p)| (r.to_string(), p)).collect();
+        let types = TypeScope {
+            enums: &[],
+            types: &[],
+        };
+        let scope = ProtocolScope {
+            ports: &mut ports,
+            members: &mut members,
+            types: &types,
+            has_clock,
+            use_clock_block: true,
+        };
+        let protocol = get_protocol(&kind, &roles, scope)?;
+        Ok((protocol, ports, members))
+    }
+
+    #[test]
+    fn valid_ready_roles() {
+        let vr = |ports: &[&str], roles, has_clock| {
+            protocol(ProtocolKind::ValidReady, ports, roles, has_clock)
+        };
+        let (p, ports, _) = vr(&["data [15:0]", "data_vld", "data_rdy"], vec![], true).unwrap();
+        assert_eq!(p.fields["payload"].len(), 1);
+        let directions: Vec<PortDirection> = ports
+            .iter()
+            .map(|p| p.properties.direction.clone())
+            .collect();
+        assert_eq!(
+            directions,
+            vec![
+                PortDirection::OUTPUT,
+                PortDirection::OUTPUT,
+                PortDirection::INPUT
+            ]
+        );
+
+        let roles = vec![
+            ("valid", Role::Port("v".to_string())),
+            ("ready", Role::Port("ack".to_string())),
+            ("payload", Role::Ports(vec!["data".to_string()])),
+        ];
+        assert!(vr(&["data [15:0]", "v", "ack"], roles, true).is_ok());
+
+        let failing = vec![
+            (vec!["data [15:0]", "data_vld", "data_rdy"], vec![], false),
+            (
+                vec!["data [15:0]", "data_vld", "data_rdy"],
+                vec![("vald", Role::Port("data_vld".to_string()))],
+                true,
+            ),
+            (vec!["data [15:0]", "data_vld"], vec![], true),
+            (
+                vec!["data [15:0]", "data_vld [1:0]", "data_rdy"],
+                vec![],
+                true,
+            ),
+            (vec!["data [15:0]", "data_vld in", "data_rdy"], vec![], true),
+            (vec!["dat [15:0]", "data_vld", "data_rdy"], vec![], true),
+            (vec!["tag", "data_vld", "data_rdy"], vec![], true),
+            (
+                vec!["data [15:0]", "data_vld", "data_rdy"],
+                vec![("ready", Role::Port("data_vld".to_string()))],
+                true,
+            ),
+        ];
+        for (ports, roles, has_clock) in failing {
+            assert!(vr(&ports, roles, has_clock).is_err(), "{:?}", ports);
+        }
+    }
+
+    #[test]
+    fn apb_roles() {
+        let ports = [
+            "s_paddr [11:0]",
+            "s_psel",
+            "s_penable",
+            "s_pwrite",
+            "s_pwdata [15:0]",
+            "s_prdata [15:0]",
+            "s_pready",
+        ];
+        let (p, _, _) = protocol(ProtocolKind::Apb, &ports, vec![], true).unwrap();
+        assert_eq!(p.signals["psel"], "s_psel");
+        assert_eq!(p.fields["paddr"][0].member, "addr");
+        assert_eq!(p.fields["prdata"][0].member, "data");
+        assert_eq!(p.fields["prdata"][0].width, 16);
+        assert!(!p.fields.contains_key("pslverr"));
+
+        let mut with_slverr = ports.to_vec();
+        with_slverr.push("s_pslverr");
+        let (p, _, _) = protocol(ProtocolKind::Apb, &with_slverr, vec![], true).unwrap();
+        assert_eq!(p.fields["pslverr"][0].member, "slverr");
+
+        let missing_pready = &ports[..6];
+        assert!(protocol(ProtocolKind::Apb, missing_pready, vec![], true).is_err());
+        let roles = vec![("paddr", Role::Port("s_prdata".to_string()))];
+        assert!(protocol(ProtocolKind::Apb, &ports, roles, true).is_err());
+    }
+
+    #[test]
+    fn axi_roles_and_members() {
+        let ports = [
+            "m_axis_tdata [15:0]",
+            "m_axis_tlast",
+            "m_axis_tuser [3:0]",
+            "m_axis_tvalid",
+            "m_axis_tready",
+        ];
+        let (p, _, members) = protocol(ProtocolKind::Axi4Stream, &ports, vec![], true).unwrap();
+        assert_eq!(p.fields["tdata"][0].member, "data");
+        assert!(!p.fields.contains_key("tkeep"));
+        let generated: Vec<String> = members[MEMBERS.len()..]
+            .iter()
+            .map(|m| format!("{} {}", m.kind, m.name))
+            .collect();
+        assert_eq!(generated, vec!["bit last", "bit [3:0] user"]);
+        assert!(members.last().unwrap().is_randomized);
+
+        let ports = [
+            "awaddr [11:0]",
+            "awvalid",
+            "awready",
+            "wdata [15:0]",
+            "wvalid",
+            "wready",
+            "bresp [1:0]",
+            "bvalid",
+            "bready",
+            "araddr [11:0]",
+            "arvalid",
+            "arready",
+            "rdata [15:0]",
+            "rresp [1:0]",
+            "rvalid",
+            "rready",
+        ];
+        let (p, _, members) = protocol(ProtocolKind::Axi4Lite, &ports, vec![], true).unwrap();
+        assert_eq!(p.fields["bresp"][0].member, "resp");
+        assert_eq!(members.len(), MEMBERS.len() + 1);
+        assert!(!members.last().unwrap().is_randomized);
+        assert!(protocol(ProtocolKind::Axi4Lite, &ports[1..], vec![], true).is_err());
+    }
+
+    #[test]
+    fn serial_roles_and_members() {
+        let (p, ports, members) =
+            protocol(ProtocolKind::Uart, &["uart_txd", "uart_rxd"], vec![], false).unwrap();
+        assert!(!p.is_clocked);
+        assert_eq!(p.signals["rx"], "uart_rxd");
+        assert_eq!(ports[1].properties.direction, PortDirection::INPUT);
+        assert_eq!(members.len(), MEMBERS.len() + 3);
+
+        let (p, _, _) =
+            protocol(ProtocolKind::Spi, &["sck", "ss_n", "mosi"], vec![], false).unwrap();
+        assert_eq!(p.signals["cs"], "ss_n");
+        assert!(!p.signals.contains_key("miso"));
+        assert!(protocol(ProtocolKind::Spi, &["sck", "mosi", "miso"], vec![], false).is_err());
+    }
+}
@@ -0,0 +1,151 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::config::vip::Sequence as Sequencecfg;
+use crate::dut::utils::{fmt_suggestion, get_closest_name};
+use crate::render::member::is_identifier;
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct Sequence {
+    pub name: String,
+    pub count: u32,
+    pub constraints: Vec<String>,
+    pub delay: Option<(u32, u32)>,
+    pub sequences: Vec<String>,
+}
+
+#[derive(Debug, Error)]
+pub enum ParseSequenceError {
+    #[error("invalid sequence name {0}")]
+    InvalidSequenceName(String),
+
+    #[error("sequence {0} declared several times")]
+    DuplicatedSequence(String),
+
+    #[error("unknown sequence {nested} in sequence {name} (sequences can only start sequences declared before them){}", fmt_suggestion(.suggestion))]
+    UnknownSequence {
+        name: String,
+        nested: String,
+        suggestion: Option<String>,
+    },
+
+    #[error("invalid delay in sequence {name} (expected: [<min>, <max>] with min <= max, found: [{min}, {max}])")]
+    InvalidDelay { name: String, min: u32, max: u32 },
+
+    #[error("sequence {0} delay requires a VIP clock")]
+    DelayWithoutClock(String),
+
+    #[error("empty constraint in sequence {0}")]
+    EmptyConstraint(String),
+}
+
+pub fn get_sequences(
+    sequences: &[Sequencecfg],
+    has_clock: bool,
+) -> Result<Vec<Sequence>, ParseSequenceError> {
+    let mut render_sequences: Vec<Sequence> = Vec::new();
+    for s in sequences {
+        if !is_identifier(&s.name) || ["default", "responder"].contains(&s.name.as_str()) {
+            return Err(ParseSequenceError::InvalidSequenceName(s.name.clone()));
+        }
+        if render_sequences.iter().any(|r| r.name == s.name) {
+            return Err(ParseSequenceError::DuplicatedSequence(s.name.clone()));
+        }
+
+        let declared: Vec<String> = render_sequences.iter().map(|r| r.name.clone()).collect();
+        for n in &s.sequences {
+            if !declared.contains(n) {
+                return Err(ParseSequenceError::UnknownSequence {
+                    name: s.name.clone(),
+                    nested: n.clone(),
+                    suggestion: get_closest_name(n, &declared),
+                });
+            }
+        }
+
+        match s.delay {
+            Some((min, max)) if min > max => {
+                return Err(ParseSequenceError::InvalidDelay {
+                    name: s.name.clone(),
+                    min,
+                    max,
+                })
+            }
+            Some(_) if !has_clock => {
+                return Err(ParseSequenceError::DelayWithoutClock(s.name.clone()))
+            }
+            _ => (),
+        }
+
+        let mut constraints = Vec::new();
+        for c in &s.constraints {
+            let c = c.trim().trim_end_matches(';').trim();
+            if c.is_empty() {
+                return Err(ParseSequenceError::EmptyConstraint(s.name.clone()));
+            }
+            constraints.push(format!("{};", c));
+        }
+
+        render_sequences.push(Sequence {
+            name: s.name.clone(),
+            count: s.count,
+            constraints,
+            delay: s.delay,
+            sequences: s.sequences.clone(),
+        });
+    }
+    Ok(render_sequences)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::get_sequences;
+    use crate::config::vip::Sequence as Sequencecfg;
+
+    fn sequence(name: &str, delay: Option<(u32, u32)>, sequences: Vec<&str>) -> Sequencecfg {
+        Sequencecfg {
+            name: name.to_string(),
+            count: 4,
+            constraints: vec!["len < 4".to_string(), " op == WRITE; ".to_string()],
+            delay,
+            sequences: sequences.into_iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn sequences_library() {
+        let cfg = vec![
+            sequence("write", None, vec![]),
+            sequence("burst", Some((0, 3)), vec!["write"]),
+        ];
+        let sequences = get_sequences(&cfg, true).unwrap();
+        assert_eq!(sequences.len(), 2);
+        assert_eq!(sequences[0].constraints, vec!["len < 4;", "op == WRITE;"]);
+        assert_eq!(sequences[1].sequences, vec!["write"]);
+
+        let failing = vec![
+            (vec![sequence("default", None, vec![])], true),
+            (vec![sequence("responder", None, vec![])], true),
+            (vec![sequence("1st", None, vec![])], true),
+            (
+                vec![
+                    sequence("write", None, vec![]),
+                    sequence("write", None, vec![]),
+                ],
+                true,
+            ),
+            (
+                vec![
+                    sequence("burst", None, vec!["write"]),
+                    sequence("write", None, vec![]),
+                ],
+                true,
+            ),
+            (vec![sequence("burst", Some((4, 2)), vec![])], true),
+            (vec![sequence("burst", Some((0, 2)), vec![])], false),
+        ];
+        for (cfg, has_clock) in failing {
+            assert!(get_sequences(&cfg, has_clock).is_err(), "{:?}", cfg);
+        }
+    }
+}
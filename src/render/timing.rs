@@ -0,0 +1,392 @@
+use log::error;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::config::project::Timescale as Timescalecfg;
+use crate::config::vip::{
+    ClockEdge, Clocking as Clockingcfg, ClockingBlock as ClockingBlockcfg, ResetPolarity,
+    VIP as VIPcfg,
+};
+use crate::dut::utils::{fmt_suggestion, get_closest_name};
+use crate::render::vip::VIPPort;
+
+const TIME_UNITS: [&str; 6] = ["s", "ms", "us", "ns", "ps", "fs"];
+// harness clk period, in timeunits
+pub const DEFAULT_CLOCK_PERIOD: u32 = 20;
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct Timescale {
+    pub unit: String,
+    pub precision: String,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct Clock {
+    pub name: String,
+    pub period: u32,
+    // clocking blocks names prefix, empty for the first clock
+    pub prefix: String,
+    // ports driven and sampled on this clock
+    pub ports: Vec<String>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct Reset {
+    pub name: String,
+    // clock domain, none for VIPs without clock
+    pub clock: Option<String>,
+    pub is_active_high: bool,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct Clocking {
+    pub cont_drv: ClockingBlock,
+    pub resp_drv: ClockingBlock,
+    pub mon: ClockingBlock,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct ClockingBlock {
+    // clocking event edge, e.g. 'posedge' or 'edge' for both
+    pub edge: String,
+    // skews without '#', e.g. '1step' or '2ns'
+    pub input_skew: Option<String>,
+    pub output_skew: Option<String>,
+}
+
+#[derive(Debug, Error)]
+pub enum ParseTimingError {
+    #[error("invalid time {0} (expected: 1, 10 or 100 followed by s, ms, us, ns, ps or fs)")]
+    InvalidTime(String),
+
+    #[error("time precision {precision} is coarser than time unit {unit}")]
+    InvalidPrecision { unit: String, precision: String },
+
+    #[error("{0} and {0}s are exclusive")]
+    ExclusiveClocks(String),
+
+    #[error("clock or reset {0} declared several times or also declared as a port")]
+    DuplicatedName(String),
+
+    #[error("clock {clock} period {period} must be even and non zero")]
+    InvalidPeriod { clock: String, period: u32 },
+
+    #[error("unknown clock {clock} for {context}{}", fmt_suggestion(.suggestion))]
+    UnknownClock {
+        context: String,
+        clock: String,
+        suggestion: Option<String>,
+    },
+
+    #[error("invalid {block} skew {skew} (expected: 1step for inputs, or a delay such as 0, 2 or 500ps)")]
+    InvalidSkew { block: String, skew: String },
+}
+
+pub fn get_timescale(timescale: &Timescalecfg) -> Result<Timescale, ParseTimingError> {
+    let unit = get_time_exponent(&timescale.unit)?;
+    let precision = get_time_exponent(&timescale.precision)?;
+    if precision > unit {
+        return Err(ParseTimingError::InvalidPrecision {
+            unit: timescale.unit.clone(),
+            precision: timescale.precision.clone(),
+        });
+    }
+
+    Ok(Timescale {
+        unit: timescale.unit.clone(),
+        precision: timescale.precision.clone(),
+    })
+}
+
+pub fn get_render_timescale(timescale: &Timescalecfg) -> Timescale {
+    get_timescale(timescale).unwrap_or_else(|e| {
+        error!("project timescale: {}", e);
+        panic!("bad project timescale");
+    })
+}
+
+// power of ten of the time in seconds, e.g. -8 for '10ns'
+fn get_time_exponent(time: &str) -> Result<i32, ParseTimingError> {
+    let invalid = || ParseTimingError::InvalidTime(time.to_string());
+    let split = time
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(invalid)?;
+    let (magnitude, unit) = time.split_at(split);
+    let magnitude = match magnitude {
+        "1" => 0,
+        "10" => 1,
+        "100" => 2,
+        _ => return Err(invalid()),
+    };
+    let unit = TIME_UNITS
+        .iter()
+        .position(|u| *u == unit)
+        .ok_or_else(invalid)? as i32;
+    Ok(magnitude - 3 * unit)
+}
+
+pub fn get_clock_domains(
+    vip: &VIPcfg,
+    ports: &[VIPPort],
+) -> Result<(Vec<Clock>, Vec<Reset>), ParseTimingError> {
+    let clocks: Vec<(String, Option<u32>)> = match (&vip.clock, vip.clocks.is_empty()) {
+        (Some(_), false) => return Err(ParseTimingError::ExclusiveClocks("clock".to_string())),
+        (Some(clock), true) => vec![(clock.clone(), None)],
+        (None, _) => vip
+            .clocks
+            .iter()
+            .map(|c| (c.name.clone(), c.period))
+            .collect(),
+    };
+    let is_active_high = |polarity: &Option<ResetPolarity>| {
+        polarity.as_ref().or(vip.reset_polarity.as_ref()) == Some(&ResetPolarity::High)
+    };
+    let resets: Vec<(String, Option<String>, bool)> = match (&vip.reset, vip.resets.is_empty()) {
+        (Some(_), false) => return Err(ParseTimingError::ExclusiveClocks("reset".to_string())),
+        (Some(reset), true) => vec![(reset.clone(), None, is_active_high(&None))],
+        (None, _) => vip
+            .resets
+            .iter()
+            .map(|r| (r.name.clone(), r.clock.clone(), is_active_high(&r.polarity)))
+            .collect(),
+    };
+
+    let mut names: Vec<String> = ports.iter().map(|p| p.name.clone()).collect();
+    for name in clocks
+        .iter()
+        .map(|(c, _)| c)
+        .chain(resets.iter().map(|(r, _, _)| r))
+    {
+        if names.contains(name) {
+            return Err(ParseTimingError::DuplicatedName(name.clone()));
+        }
+        names.push(name.clone());
+    }
+
+    let clocks_names: Vec<String> = clocks.iter().map(|(c, _)| c.clone()).collect();
+    let get_domain = |context: String, clock: &Option<String>| match clock {
+        Some(clock) if !clocks_names.contains(clock) => Err(ParseTimingError::UnknownClock {
+            context,
+            clock: clock.clone(),
+            suggestion: get_closest_name(clock, &clocks_names),
+        }),
+        Some(clock) => Ok(Some(clock.clone())),
+        None => Ok(clocks_names.first().cloned()),
+    };
+
+    let mut render_clocks = Vec::new();
+    for (i, (name, period)) in clocks.iter().enumerate() {
+        let period = period.unwrap_or(DEFAULT_CLOCK_PERIOD);
+        if period == 0 || period % 2 != 0 {
+            return Err(ParseTimingError::InvalidPeriod {
+                clock: name.clone(),
+                period,
+            });
+        }
+        render_clocks.push(Clock {
+            name: name.clone(),
+            period,
+            prefix: if i == 0 {
+                "".to_string()
+            } else {
+                format!("{}_", name)
+            },
+            ports: Vec::new(),
+        });
+    }
+    for p in ports {
+        if let Some(domain) = get_domain(format!("port {}", p.name), &p.clock)? {
+            let clock = render_clocks.iter_mut().find(|c| c.name == domain).unwrap();
+            clock.ports.push(p.name.clone());
+        }
+    }
+
+    let mut render_resets = Vec::new();
+    for (name, clock, is_active_high) in resets {
+        render_resets.push(Reset {
+            clock: get_domain(format!("reset {}", name), &clock)?,
+            name,
+            is_active_high,
+        });
+    }
+
+    Ok((render_clocks, render_resets))
+}
+
+pub fn get_clocking(clocking: &Clockingcfg) -> Result<Clocking, ParseTimingError> {
+    Ok(Clocking {
+        cont_drv: get_clocking_block("cb_cont_drv", &clocking.cont_drv, clocking)?,
+        resp_drv: get_clocking_block("cb_resp_drv", &clocking.resp_drv, clocking)?,
+        mon: get_clocking_block("cb_mon", &clocking.mon, clocking)?,
+    })
+}
+
+fn get_clocking_block(
+    name: &str,
+    block: &ClockingBlockcfg,
+    defaults: &Clockingcfg,
+) -> Result<ClockingBlock, ParseTimingError> {
+    let edge = match block.edge.as_ref().or(defaults.edge.as_ref()) {
+        Some(ClockEdge::Posedge) | None => "posedge",
+        Some(ClockEdge::Negedge) => "negedge",
+        Some(ClockEdge::Both) => "edge",
+    };
+
+    let input_skew = block.input_skew.clone().or(defaults.input_skew.clone());
+    let output_skew = block.output_skew.clone().or(defaults.output_skew.clone());
+    for (skew, is_input) in [(&input_skew, true), (&output_skew, false)] {
+        match skew {
+            Some(skew) if !is_skew(skew, is_input) => {
+                return Err(ParseTimingError::InvalidSkew {
+                    block: name.to_string(),
+                    skew: skew.clone(),
+                })
+            }
+            _ => (),
+        }
+    }
+
+    Ok(ClockingBlock {
+        edge: edge.to_string(),
+        input_skew,
+        output_skew,
+    })
+}
+
+// expects '1step' (inputs only) or '<number><time unit (opt)>'
+fn is_skew(skew: &str, is_input: bool) -> bool {
+    if skew == "1step" {
+        return is_input;
+    }
+    // two letter units first
+    let number = TIME_UNITS
+        .iter()
+        .rev()
+        .find_map(|u| skew.strip_suffix(u))
+        .unwrap_or(skew);
+    let mut split = number.splitn(2, '.');
+    let integer = split.next().unwrap_or_default();
+    let fraction = split.next();
+    !integer.is_empty()
+        && integer.chars().all(|c| c.is_ascii_digit())
+        && fraction.is_none_or(|f| !f.is_empty() && f.chars().all(|c| c.is_ascii_digit()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_clock_domains, get_clocking, get_timescale};
+    use crate::config::project::Timescale as Timescalecfg;
+    use crate::config::vip::{ClockEdge, Clocking as Clockingcfg, ClockingBlock, VIP as VIPcfg};
+    use crate::render::vip::VIPPort;
+
+    fn timescale(unit: &str, precision: &str) -> Timescalecfg {
+        Timescalecfg {
+            unit: unit.to_string(),
+            precision: precision.to_string(),
+        }
+    }
+
+    #[test]
+    fn timescales() {
+        assert!(get_timescale(&Timescalecfg::default()).is_ok());
+        assert!(get_timescale(&timescale("100ps", "10ps")).is_ok());
+        for (unit, precision) in [
+            ("1ps", "1ns"),
+            ("2ns", "1ps"),
+            ("1ns", "1 ps"),
+            ("ns", "ps"),
+        ] {
+            assert!(get_timescale(&timescale(unit, precision)).is_err());
+        }
+    }
+
+    #[test]
+    fn clocking_blocks() {
+        let cfg = Clockingcfg {
+            edge: Some(ClockEdge::Negedge),
+            input_skew: Some("1step".to_string()),
+            output_skew: Some("0.5ns".to_string()),
+            mon: ClockingBlock {
+                edge: Some(ClockEdge::Both),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let clocking = get_clocking(&cfg).unwrap();
+        assert_eq!(clocking.cont_drv.edge, "negedge");
+        assert_eq!(clocking.mon.edge, "edge");
+        assert_eq!(clocking.mon.output_skew, Some("0.5ns".to_string()));
+        assert_eq!(
+            get_clocking(&Clockingcfg::default()).unwrap().resp_drv.edge,
+            "posedge"
+        );
+
+        for (input_skew, output_skew) in
+            [("1step", "1step"), ("ns", "0"), ("1.ns", "0"), ("-1", "0")]
+        {
+            let cfg = Clockingcfg {
+                input_skew: Some(input_skew.to_string()),
+                output_skew: Some(output_skew.to_string()),
+                ..Default::default()
+            };
+            assert!(
+                get_clocking(&cfg).is_err(),
+                "{} {}",
+                input_skew,
+                output_skew
+            );
+        }
+    }
+
+    #[test]
+    fn clock_domains() {
+        let domains = |description: &str| {
+            let vip: VIPcfg = toml::from_str(description).unwrap();
+            let ports: Vec<VIPPort> = vip.ports.iter().map(|p| p.try_into().unwrap()).collect();
+            get_clock_domains(&vip, &ports)
+        };
+
+        let (clocks, resets) = domains(
+            r#"
+            ports = ["wdata", { name = "rdata", clock = "rclk" }]
+            clocks = [{ name = "wclk" }, { name = "rclk", period = 30 }]
+            resets = [{ name = "wrst_n" }, { name = "rrst", clock = "rclk", polarity = "high" }]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(clocks[0].ports, vec!["wdata"]);
+        assert_eq!(clocks[1].prefix, "rclk_");
+        assert_eq!(clocks[1].period, 30);
+        assert_eq!(resets[0].clock, Some("wclk".to_string()));
+        assert_eq!(resets[1].clock, Some("rclk".to_string()));
+        assert!(!resets[0].is_active_high && resets[1].is_active_high);
+
+        let (clocks, resets) = domains(
+            r#"ports = ["data"]
+            reset = "rst"
+            reset_polarity = "high""#,
+        )
+        .unwrap();
+        assert!(clocks.is_empty());
+        assert_eq!(resets[0].clock, None);
+        assert!(resets[0].is_active_high);
+
+        let failing = vec![
+            r#"ports = ["data"]
+            clock = "clk"
+            clocks = [{ name = "clk2" }]"#,
+            r#"ports = ["clk"]
+            clock = "clk""#,
+            r#"ports = ["data"]
+            clocks = [{ name = "clk", period = 15 }]"#,
+            r#"ports = [{ name = "data", clock = "clk2" }]
+            clock = "clk""#,
+            r#"ports = ["data"]
+            clock = "clk"
+            resets = [{ name = "rst_n", clock = "clkk" }]"#,
+        ];
+        for description in failing {
+            assert!(domains(description).is_err(), "{}", description);
+        }
+    }
+}
@@ -1,11 +1,291 @@
+use std::collections::{BTreeSet, HashMap};
+
 use serde::{Deserialize, Serialize};
 
+use crate::config::{instance::Instances, project::DUT as DUTcfg};
+use crate::dut::connection::{
+    get_unconnected_ranges, parse_constant, ConnectionExpr, ConnectionTerm,
+};
+use crate::dut::utils::{Port, PortDirection, DUT};
+use crate::render::timing::{Timescale, DEFAULT_CLOCK_PERIOD};
+use crate::render::vip::VIP;
+
 #[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct Top {
     pub name: String,
     pub default_sequence_repeat: u32,
+    pub timescale: Timescale,
 
     pub dut_name: String,
     pub dut_clk: Option<String>,
     pub dut_rst: Option<String>,
+    pub dut_clk_rst: Vec<HarnessConnection>,
+    pub dut_wires: Vec<Port>,
+    pub dut_tie_offs: Vec<TieOff>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct TieOff {
+    pub dut_expr: String,
+    pub value: String,
+    pub is_whole_port: bool,
+}
+
+// harness clock other than clk, with its synchronously released reset
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct HarnessClock {
+    pub name: String,
+    pub half_period: u32,
+    pub reset: String,
+}
+
+// VIP interface clock or reset port driven by a harness signal
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct HarnessConnection {
+    pub port: String,
+    pub signal: String,
+}
+
+fn get_harness_clock_name(period: u32) -> String {
+    if period == DEFAULT_CLOCK_PERIOD {
+        "clk".to_string()
+    } else {
+        format!("clk_{}", period)
+    }
+}
+
+fn get_harness_reset_name(period: u32) -> String {
+    if period == DEFAULT_CLOCK_PERIOD {
+        "rst".to_string()
+    } else {
+        format!("rst_{}", period)
+    }
+}
+
+// VIPs whose monitors report resets to the scoreboard
+pub fn get_reset_vips(vips: &[VIP]) -> Vec<String> {
+    vips.iter()
+        .filter(|v| v.reset.is_some())
+        .map(|v| v.name.clone())
+        .collect()
+}
+
+pub fn get_harness_clocks(vips: &[VIP]) -> Vec<HarnessClock> {
+    let periods: BTreeSet<u32> = vips
+        .iter()
+        .flat_map(|v| &v.clocks)
+        .map(|c| c.period)
+        .filter(|p| *p != DEFAULT_CLOCK_PERIOD)
+        .collect();
+    periods
+        .into_iter()
+        .map(|p| HarnessClock {
+            name: get_harness_clock_name(p),
+            half_period: p / 2,
+            reset: get_harness_reset_name(p),
+        })
+        .collect()
+}
+
+// clocks and resets harness connections per VIP, each reset following its clock domain
+pub fn get_vips_clk_rst_ports(
+    vips: &[VIP],
+) -> (
+    HashMap<String, Vec<HarnessConnection>>,
+    HashMap<String, Vec<HarnessConnection>>,
+) {
+    let mut vips_clk = HashMap::new();
+    let mut vips_rst = HashMap::new();
+    for v in vips {
+        let clocks = v
+            .clocks
+            .iter()
+            .map(|c| HarnessConnection {
+                port: c.name.clone(),
+                signal: get_harness_clock_name(c.period),
+            })
+            .collect();
+        vips_clk.insert(v.name.clone(), clocks);
+
+        let resets = v
+            .resets
+            .iter()
+            .map(|r| {
+                let period = v
+                    .clocks
+                    .iter()
+                    .find(|c| Some(&c.name) == r.clock.as_ref())
+                    .map_or(DEFAULT_CLOCK_PERIOD, |c| c.period);
+                // harness resets are active low
+                let signal = get_harness_reset_name(period);
+                HarnessConnection {
+                    port: r.name.clone(),
+                    signal: if r.is_active_high {
+                        format!("~{}", signal)
+                    } else {
+                        signal
+                    },
+                }
+            })
+            .collect();
+        vips_rst.insert(v.name.clone(), resets);
+    }
+
+    (vips_clk, vips_rst)
+}
+
+pub fn get_dut_wires(instances: &Instances, dut: &DUT) -> Vec<Port> {
+    let mut names = BTreeSet::new();
+    for i in &instances.instances {
+        for c in i.connections.iter().filter(|c| !c.is_direct) {
+            if let Ok(expr) = c.dut_port.parse::<ConnectionExpr>() {
+                for t in expr.terms {
+                    if let ConnectionTerm::Port { name, range: _ } = t {
+                        names.insert(name);
+                    }
+                }
+            }
+        }
+    }
+
+    names
+        .into_iter()
+        .filter_map(|name| {
+            dut.ports.get(&name).map(|properties| Port {
+                name: name.clone(),
+                properties: properties.clone(),
+            })
+        })
+        .collect()
+}
+
+fn get_dut_connections(instances: &Instances, dut_cfg: &DUTcfg) -> Vec<String> {
+    let mut connections = instances.get_dut_connections();
+    connections.extend(dut_cfg.clock.clone());
+    connections.extend(dut_cfg.reset.clone());
+    connections
+}
+
+// DUT inputs left unconnected and named like a VIP clock or reset, driven by its harness signal
+pub fn get_dut_clk_rst_ports(
+    instances: &Instances,
+    vips: &[VIP],
+    dut: &DUT,
+    dut_cfg: &DUTcfg,
+) -> Vec<HarnessConnection> {
+    let unconnected = get_unconnected_ranges(dut, &get_dut_connections(instances, dut_cfg));
+    let (vips_clk, vips_rst) = get_vips_clk_rst_ports(vips);
+
+    let mut ports: Vec<HarnessConnection> = Vec::new();
+    for v in vips {
+        for c in vips_clk[&v.name].iter().chain(&vips_rst[&v.name]) {
+            let is_unconnected_input = match (dut.ports.get(&c.port), unconnected.get(&c.port)) {
+                (Some(properties), Some(ranges)) => {
+                    properties.direction == PortDirection::INPUT
+                        && ranges.len() == 1
+                        && ranges[0].1 - ranges[0].0 + 1 == properties.get_width()
+                }
+                _ => false,
+            };
+            // the first VIP declaring a clock or reset name drives it
+            if is_unconnected_input && !ports.iter().any(|p| p.port == c.port) {
+                ports.push(c.clone());
+            }
+        }
+    }
+    ports
+}
+
+// DUT port (or bits) not connected by any instance, nor used as DUT clock or reset
+pub struct UnconnectedPort {
+    pub port: Port,
+    pub ranges: Vec<(u32, u32)>,
+    pub is_whole_port: bool,
+}
+
+pub fn get_unconnected_ports(
+    instances: &Instances,
+    vips: &[VIP],
+    dut: &DUT,
+    dut_cfg: &DUTcfg,
+) -> Vec<UnconnectedPort> {
+    let mut connections = get_dut_connections(instances, dut_cfg);
+    connections.extend(
+        get_dut_clk_rst_ports(instances, vips, dut, dut_cfg)
+            .into_iter()
+            .map(|c| c.port),
+    );
+
+    get_unconnected_ranges(dut, &connections)
+        .into_iter()
+        .map(|(name, ranges)| {
+            let properties = dut.ports[&name].clone();
+            let is_whole_port =
+                ranges.len() == 1 && ranges[0].1 - ranges[0].0 + 1 == properties.get_width();
+            UnconnectedPort {
+                port: Port { name, properties },
+                ranges,
+                is_whole_port,
+            }
+        })
+        .collect()
+}
+
+pub fn get_dut_tie_offs(
+    instances: &Instances,
+    vips: &[VIP],
+    dut: &DUT,
+    dut_cfg: &DUTcfg,
+) -> Vec<TieOff> {
+    let mut tie_offs = Vec::new();
+    for u in get_unconnected_ports(instances, vips, dut, dut_cfg) {
+        let name = u.port.name.clone();
+        let properties = &u.port.properties;
+
+        // invalid values are reported while checking unconnected DUT ports, outputs left open
+        let value = match properties.direction {
+            PortDirection::INPUT => Some(
+                dut_cfg
+                    .tie_offs
+                    .get(&name)
+                    .and_then(|v| parse_constant(v.trim()).ok())
+                    .unwrap_or(ConnectionTerm::Constant {
+                        value: "'0".to_string(),
+                        width: None,
+                    }),
+            ),
+            _ if u.is_whole_port => None,
+            _ => continue,
+        };
+
+        if u.is_whole_port {
+            tie_offs.push(TieOff {
+                dut_expr: name,
+                value: value.map_or("".to_string(), |v| v.to_string()),
+                is_whole_port: true,
+            });
+        } else if let Some(value) = value {
+            // ranges are bit indices of single dimension ports
+            let offset = match properties.dimensions[..] {
+                [(end, start)] => end.min(start),
+                _ => 0,
+            };
+            for (low, high) in u.ranges {
+                let range = Some((high, low));
+                let dut_expr = ConnectionTerm::Port {
+                    name: name.clone(),
+                    range,
+                };
+                let value = value
+                    .get_constant_slice(properties.get_width(), low - offset, high - offset)
+                    .unwrap_or_else(|| value.clone());
+                tie_offs.push(TieOff {
+                    dut_expr: dut_expr.to_string(),
+                    value: value.to_string(),
+                    is_whole_port: false,
+                });
+            }
+        }
+    }
+    tie_offs
 }
@@ -1,5 +1,4 @@
-use std::collections::HashMap;
-use std::iter::zip;
+use std::collections::{BTreeMap, HashMap};
 use std::str::FromStr;
 
 use log::{debug, error, warn};
@@ -11,222 +10,720 @@ use crate::config::{
         Instance, Instances,
         Mode::{Controller, Passive},
     },
-    vip::VIP as VIPcfg,
+    vip::{Bound, Port as Portcfg, VIP as VIPcfg},
+};
+use crate::dut::connection::{ConnectionExpr, ConnectionTerm};
+use crate::dut::utils::{ParsePortError, Port, PortDirection, PortProperties, DUT};
+use crate::render::constraint::{get_constraints, Constraint, ParseConstraintError};
+use crate::render::coverage::{get_coverage, Coverage, ParseCoverageError};
+use crate::render::mapping::{get_mapping, Mapping, MappingScope, ParseMappingError};
+use crate::render::member::{
+    EnumType, Member, ParseEnumError, ParseMemberError, ParseTypeError, TypeScope, UserType,
+};
+use crate::render::parameter::{
+    evaluate, get_parameter_ports, get_parameters, get_specialization, get_values, Parameter,
+    ParseParameterError,
+};
+use crate::render::protocol::{get_protocol, ParseProtocolError, Protocol, ProtocolScope};
+use crate::render::sequence::{get_sequences, ParseSequenceError, Sequence};
+use crate::render::timing::{
+    get_clock_domains, get_clocking, Clock, Clocking, ParseTimingError, Reset,
 };
-use crate::dut::utils::{ParsePortError, Port, PortDirection, DUT};
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct VIP {
     pub name: String,
-    pub ports: Vec<Port>,
+    pub parameters: Vec<Parameter>,
+    // e.g. ' #(int DATA_W = 16)' and ' #(.DATA_W(DATA_W))', empty without parameters
+    pub parameter_ports: String,
+    pub specialization: String,
+    pub ports: Vec<VIPPort>,
+    // first clock and reset
     pub clock: Option<String>,
     pub reset: Option<String>,
+    pub clocks: Vec<Clock>,
+    pub resets: Vec<Reset>,
     pub use_clock_block: bool,
+    pub clocking: Clocking,
+    pub protocol: Option<Protocol>,
+    pub mapping: Option<Mapping>,
 
     pub item: Item,
+    pub types: Vec<UserType>,
+    pub coverage: Coverage,
+    pub sequences: Vec<Sequence>,
 }
 
 #[derive(Debug, Error)]
 pub enum ParseVIPError {
-    #[error("invalid port")]
+    #[error("invalid port: {0}")]
     PortError(#[from] ParsePortError),
 
-    #[error("invalid member")]
+    #[error("invalid parameter: {0}")]
+    ParameterError(#[from] ParseParameterError),
+
+    #[error("invalid member: {0}")]
     MemberError(#[from] ParseMemberError),
+
+    #[error("invalid enum: {0}")]
+    EnumError(#[from] ParseEnumError),
+
+    #[error("invalid constraint: {0}")]
+    ConstraintError(#[from] ParseConstraintError),
+
+    #[error("invalid coverage: {0}")]
+    CoverageError(#[from] ParseCoverageError),
+
+    #[error("invalid clocking: {0}")]
+    TimingError(#[from] ParseTimingError),
+
+    #[error("invalid protocol: {0}")]
+    ProtocolError(#[from] ParseProtocolError),
+
+    #[error("invalid port mapping: {0}")]
+    MappingError(#[from] ParseMappingError),
+
+    #[error("invalid sequence: {0}")]
+    SequenceError(#[from] ParseSequenceError),
+
+    #[error("invalid type: {0}")]
+    TypeError(#[from] ParseTypeError),
+
+    #[error("type {0} declared several times")]
+    DuplicatedType(String),
+
+    #[error("port {port} in clock domain {clock} cannot be driven by a protocol or mapped to a member (first clock only)")]
+    InvalidClockDomain { port: String, clock: String },
+
+    #[error("port {port} type {kind} is not a packed type of known width (or has dimensions)")]
+    InvalidPortType { port: String, kind: String },
 }
 
 impl TryFrom<&VIPcfg> for VIP {
     type Error = ParseVIPError;
 
     fn try_from(vip: &VIPcfg) -> Result<Self, Self::Error> {
+        let parameters = get_parameters(&vip.parameters)?;
+        let values = get_values(&parameters, &BTreeMap::new());
+
         let mut ports = Vec::new();
         for p in &vip.ports {
-            let port: Port = match p.parse() {
+            let mut port = match VIPPort::try_from(p) {
                 Ok(port) => port,
                 Err(e) => return Err(Self::Error::PortError(e)),
             };
+            port.set_dimensions(&values)?;
             ports.push(port);
         }
 
+        let mut enums: Vec<EnumType> = Vec::new();
+        for e in &vip.item.enums {
+            let enum_type = EnumType::try_from(e)?;
+            if enums.iter().any(|e| e.name == enum_type.name) {
+                return Err(Self::Error::DuplicatedType(enum_type.name));
+            }
+            enums.push(enum_type);
+        }
+
+        let declared: Vec<String> = vip.types.iter().map(|t| t.name.clone()).collect();
+        let mut types: Vec<UserType> = Vec::new();
+        for t in &vip.types {
+            let user_type = UserType::try_from(t)?;
+            let scope = TypeScope {
+                enums: &enums,
+                types: &types,
+            };
+            if scope.is_declared(&user_type.name) {
+                return Err(Self::Error::DuplicatedType(user_type.name));
+            }
+            user_type.check(&scope, &declared)?;
+            types.push(user_type);
+        }
+        let scope = TypeScope {
+            enums: &enums,
+            types: &types,
+        };
+
         let mut members = Vec::new();
         for m in &vip.item.members {
-            let member: Member = match m.parse() {
+            let mut member = match Member::try_from(m) {
                 Ok(member) => member,
                 Err(e) => return Err(Self::Error::MemberError(e)),
             };
+            member.fields = scope.get_fields(&member);
             members.push(member);
         }
 
+        for p in &mut ports {
+            let kind = match &p.kind {
+                Some(kind) => kind,
+                None => continue,
+            };
+            let width = format!("{} {}", kind, p.name)
+                .parse::<Member>()
+                .ok()
+                .and_then(|m| scope.get_width(&m));
+            match width {
+                Some(width) if p.properties.dimensions.is_empty() => {
+                    p.properties.dimensions = vec![(width - 1, 0)];
+                }
+                _ => {
+                    return Err(Self::Error::InvalidPortType {
+                        port: p.name.clone(),
+                        kind: kind.clone(),
+                    })
+                }
+            }
+        }
+
+        let (clocks, resets) = get_clock_domains(vip, &ports)?;
+        let clock = clocks.first().map(|c| c.name.clone());
+        for c in clocks.iter().skip(1) {
+            let driven = ports.iter().find(|p| {
+                c.ports.contains(&p.name) && (vip.protocol.is_some() || p.member.is_some())
+            });
+            if let Some(p) = driven {
+                return Err(Self::Error::InvalidClockDomain {
+                    port: p.name.clone(),
+                    clock: c.name.clone(),
+                });
+            }
+        }
+
+        if !(clock.is_some() && vip.use_clock_block) && vip.clocking != Default::default() {
+            warn!("clocking settings ignored without clock and clocking blocks");
+        }
+        let clocking = get_clocking(&vip.clocking)?;
+
+        let protocol = match &vip.protocol {
+            Some(kind) => Some(get_protocol(
+                kind,
+                &vip.roles,
+                ProtocolScope {
+                    ports: &mut ports,
+                    members: &mut members,
+                    types: &scope,
+                    has_clock: clock.is_some(),
+                    use_clock_block: vip.use_clock_block,
+                },
+            )?),
+            None => None,
+        };
+
+        let mapping = get_mapping(MappingScope {
+            ports: &ports,
+            members: &members,
+            types: &scope,
+            has_clock: clock.is_some() && vip.use_clock_block,
+            has_protocol: protocol.is_some(),
+        })?;
+
+        let coverage = get_coverage(&vip.coverage, &members, &enums)?;
+        let sequences = get_sequences(&vip.sequences, clock.is_some())?;
+
         let item = Item {
             members,
-            constraints: vip.item.constraints.clone(),
+            constraints: get_constraints(&vip.item.constraints)?,
+            enums,
         };
 
         Ok(VIP {
             name: vip.name.clone().unwrap(),
+            parameter_ports: get_parameter_ports(&parameters),
+            specialization: get_specialization(
+                parameters.iter().map(|p| (&p.name, p.name.clone())),
+            ),
+            parameters,
             ports,
-            clock: vip.clock.clone(),
-            reset: vip.reset.clone(),
+            reset: resets.first().map(|r| r.name.clone()),
+            clock,
+            clocks,
+            resets,
             use_clock_block: vip.use_clock_block,
+            clocking,
+            protocol,
+            mapping,
 
             item,
+            types,
+            coverage,
+            sequences,
         })
     }
 }
 
-pub fn get_render_vips(vips: &Vec<VIPcfg>) -> Vec<VIP> {
-    let mut render_vips = Vec::new();
-    for v in vips {
-        let vip = VIP::try_from(v).unwrap();
-        render_vips.push(vip);
+impl VIP {
+    // ports sized with the parameter values of an instance
+    pub fn get_instance_ports(
+        &self,
+        overrides: &BTreeMap<String, u32>,
+    ) -> Result<Vec<VIPPort>, ParseParameterError> {
+        let values = get_values(&self.parameters, overrides);
+        let mut ports = self.ports.clone();
+        for p in &mut ports {
+            p.set_dimensions(&values)?;
+        }
+        Ok(ports)
     }
-    render_vips
 }
 
-pub fn set_vips_port_dir(vips: &mut Vec<VIP>, instances: &Instances, dut: &DUT) {
-    for v in vips {
-        let instances: Vec<Instance> = instances
-            .instances
-            .clone()
-            .into_iter()
-            .filter(|instance| instance.vip_name == v.name)
-            .filter(|instance| instance.mode != Passive)
-            .collect();
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct VIPPort {
+    pub name: String,
+    pub properties: PortProperties,
+    // dimensions bounds as written when using parameters, empty otherwise
+    pub dimensions_expr: Vec<(String, String)>,
+    pub is_direction_set: bool,
+    pub kind: Option<String>,
+    // clock domain, the first clock when none
+    pub clock: Option<String>,
+    pub member: Option<String>,
+    pub qualifier: Option<String>,
+}
 
-        let mut directions = HashMap::<String, PortDirection>::new();
+impl std::fmt::Display for VIPPort {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.properties.dimensions.is_empty() {
+            write!(f, "{}", self.name)
+        } else {
+            write!(f, "{} {}", self.name, self.properties.get_dimensions_str())
+        }
+    }
+}
 
-        for i in instances {
-            let zip_ports = zip(v.ports.clone(), i.connected_to);
+impl TryFrom<&Portcfg> for VIPPort {
+    type Error = ParsePortError;
+
+    fn try_from(port: &Portcfg) -> Result<Self, Self::Error> {
+        match port {
+            Portcfg::Description(s) => s.parse(),
+            Portcfg::Table(t) => {
+                let direction = match &t.direction {
+                    Some(direction) => Some(direction.parse::<PortDirection>()?),
+                    None => None,
+                };
+                let bounds: Vec<(String, String)> = t
+                    .dimensions
+                    .iter()
+                    .map(|(end, start)| (get_bound_str(end), get_bound_str(start)))
+                    .collect();
+                let (dimensions, dimensions_expr) = get_dimensions(bounds);
+                let properties = PortProperties {
+                    direction: direction.clone().unwrap_or_default(),
+                    dimensions,
+                };
+                Ok(VIPPort {
+                    name: t.name.clone(),
+                    properties,
+                    dimensions_expr,
+                    is_direction_set: direction.is_some(),
+                    kind: t.kind.clone(),
+                    clock: t.clock.clone(),
+                    member: t.member.clone(),
+                    qualifier: t.qualifier.clone(),
+                })
+            }
+        }
+    }
+}
 
-            for (vp, dp) in zip_ports {
-                if let Some(dir) = directions.get(&vp.name) {
-                    let expected_dir = if i.mode == Controller {
-                        !dir.clone()
-                    } else {
-                        dir.clone()
-                    };
-                    let port_dir = dut.ports.get(&dp).unwrap().direction.clone();
+impl FromStr for VIPPort {
+    type Err = ParsePortError;
 
-                    if expected_dir != port_dir {
-                        error!("port_dir not matching");
-                    }
-                } else {
-                    let port_dir = dut.ports.get(&dp).unwrap().direction.clone();
-                    let dir = if i.mode == Controller {
-                        !port_dir
-                    } else {
-                        port_dir
-                    };
-                    directions.insert(vp.name, dir);
+    // expects '<port_name> <direction (opt)> <dim0> <dim1>...'
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // dimensions may hold parameter expressions with whitespaces
+        let mut depth = 0;
+        let s: String = s
+            .chars()
+            .filter(|c| {
+                match c {
+                    '[' => depth += 1,
+                    ']' => depth -= 1,
+                    _ => (),
                 }
+                depth == 0 || !c.is_whitespace()
+            })
+            .collect();
+        let mut split = s.split_whitespace().collect::<Vec<&str>>();
+
+        let direction = match split.get(1) {
+            Some(direction) if !direction.starts_with('[') => {
+                let direction = direction.parse::<PortDirection>()?;
+                split.remove(1);
+                Some(direction)
             }
-        }
+            _ => None,
+        };
 
-        for p in &mut v.ports {
-            if let Some(dir) = directions.get(&p.name) {
-                debug!("port {} direction set to {:#?}", p.name, dir);
-                p.properties.direction = dir.clone();
-            } else {
-                warn!("port {} direction not set", p.name);
+        let dimensions_start = split.len().min(1);
+        let mut bounds = Vec::new();
+        for d in split.drain(dimensions_start..) {
+            match d
+                .strip_prefix('[')
+                .and_then(|d| d.strip_suffix(']'))
+                .and_then(|d| d.split_once(':'))
+            {
+                Some((end, start)) => bounds.push((end.to_string(), start.to_string())),
+                None => return Err(Self::Err::InvalidDimDescription(d.to_string())),
             }
         }
+        let (dimensions, dimensions_expr) = get_dimensions(bounds);
+
+        let mut port: Port = split.join(" ").parse()?;
+        port.properties.dimensions = dimensions;
+        if let Some(direction) = direction.clone() {
+            port.properties.direction = direction;
+        }
+
+        Ok(VIPPort {
+            name: port.name,
+            properties: port.properties,
+            dimensions_expr,
+            is_direction_set: direction.is_some(),
+            kind: None,
+            clock: None,
+            member: None,
+            qualifier: None,
+        })
     }
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
-pub struct Item {
-    pub members: Vec<Member>,
-    pub constraints: Vec<String>,
+impl VIPPort {
+    // evaluates the dimensions written with parameters
+    pub fn set_dimensions(
+        &mut self,
+        values: &HashMap<String, u32>,
+    ) -> Result<(), ParseParameterError> {
+        if !self.dimensions_expr.is_empty() {
+            self.properties.dimensions = self
+                .dimensions_expr
+                .iter()
+                .map(|(end, start)| Ok((evaluate(end, values)?, evaluate(start, values)?)))
+                .collect::<Result<Vec<(u32, u32)>, ParseParameterError>>()?;
+        }
+        Ok(())
+    }
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
-pub struct Member {
-    name: String,
-    kind: String,
-    is_randomized: bool,
+fn get_bound_str(bound: &Bound) -> String {
+    match bound {
+        Bound::Value(value) => value.to_string(),
+        Bound::Expression(expr) => expr.clone(),
+    }
+}
+
+// numeric dimensions, or placeholders and the bounds to evaluate when using parameters
+#[allow(clippy::type_complexity)]
+fn get_dimensions(bounds: Vec<(String, String)>) -> (Vec<(u32, u32)>, Vec<(String, String)>) {
+    let numeric: Option<Vec<(u32, u32)>> = bounds
+        .iter()
+        .map(|(end, start)| Some((end.parse().ok()?, start.parse().ok()?)))
+        .collect();
+    match numeric {
+        Some(dimensions) => (dimensions, Vec::new()),
+        None => (vec![(0, 0); bounds.len()], bounds),
+    }
+}
+
+pub fn get_render_vips(vips: &Vec<VIPcfg>) -> Vec<VIP> {
+    let mut render_vips = Vec::new();
+    for v in vips {
+        let vip = VIP::try_from(v).unwrap_or_else(|e| {
+            error!("{} vip: {}", v.name.clone().unwrap_or_default(), e);
+            panic!("bad vip description");
+        });
+        render_vips.push(vip);
+    }
+    render_vips
+}
+
+struct DirectionInference {
+    direction: PortDirection,
+    instance: String,
+    dut_connection: String,
+    dut_direction: PortDirection,
+}
+
+impl std::fmt::Display for DirectionInference {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{} connected to dut {} {} implies {}",
+            self.instance, self.dut_direction, self.dut_connection, self.direction
+        )
+    }
 }
 
 #[derive(Debug, Error)]
-pub enum ParseMemberError {
-    #[error("invalid member description (expected: <rand (opt)> <type> <name>, found: {0})")]
-    InvalidMemberDescription(String),
+pub enum PortDirectionError {
+    #[error("conflicting directions (controller side) for {vip} port {port}:\n  {first}\n  {second}\nresolve it by declaring the port inout in {vip} ports (\"{declaration}\"), or by splitting {vip} into one VIP per instance")]
+    ConflictingDirections {
+        vip: String,
+        port: String,
+        first: String,
+        second: String,
+        declaration: String,
+    },
+
+    #[error("{vip} port {port} declared {declared} (controller side) but {inference}, using {direction}")]
+    DeclaredDirectionMismatch {
+        vip: String,
+        port: String,
+        declared: PortDirection,
+        inference: String,
+        direction: PortDirection,
+    },
+}
+
+pub fn set_vips_port_dir(vips: &mut Vec<VIP>, instances: &Instances, dut: &DUT, strict: bool) {
+    let mut errors = 0;
 
-    #[error("unexpected rand found (expected: <type> <name>, found: {0})")]
-    UnexpectedRand(String),
+    for v in vips {
+        for e in v.set_ports_dir(instances, dut) {
+            error!("{}", e);
+            errors += 1;
+        }
+    }
 
-    #[error("expected rand (expected: rand <type> <name>, found: {0})")]
-    RandNotFound(String),
+    if strict && errors > 0 {
+        panic!("{} vips ports directions errors in strict mode", errors);
+    }
 }
 
-impl FromStr for Member {
-    type Err = ParseMemberError;
+impl VIP {
+    // directions inferred from the DUT ports connected by the instances of the VIP
+    fn set_ports_dir(&mut self, instances: &Instances, dut: &DUT) -> Vec<PortDirectionError> {
+        let mut errors = Vec::new();
+        let instances: Vec<Instance> = instances
+            .instances
+            .clone()
+            .into_iter()
+            .filter(|instance| instance.vip_name == self.name)
+            .filter(|instance| instance.mode != Passive)
+            .collect();
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let split = s.split_whitespace().collect::<Vec<&str>>();
+        let mut directions = HashMap::<String, DirectionInference>::new();
 
-        match split.len() {
-            2 => {
-                if split[0] == "rand" {
-                    Err(Self::Err::UnexpectedRand(s.to_string()))
+        for i in instances {
+            for c in &i.connections {
+                let dut_direction = match get_connection_direction(&c.dut_port, dut) {
+                    Some(dut_direction) => dut_direction,
+                    None => {
+                        debug!("no dut port direction found for {} in {}", c.dut_port, i);
+                        continue;
+                    }
+                };
+                let direction = if i.mode == Controller {
+                    !dut_direction.clone()
                 } else {
-                    Ok(Member {
-                        name: split[1].to_string(),
-                        kind: split[0].to_string(),
-                        is_randomized: false,
-                    })
+                    dut_direction.clone()
+                };
+                let inference = DirectionInference {
+                    direction,
+                    instance: i.to_string(),
+                    dut_connection: c.dut_port.clone(),
+                    dut_direction,
+                };
+
+                match directions.get(&c.vip_port) {
+                    Some(first) if first.direction != inference.direction => {
+                        let port = self.ports.iter().find(|p| p.name == c.vip_port);
+                        let is_declared_inout = port.is_some_and(|p| {
+                            p.is_direction_set && p.properties.direction == PortDirection::INOUT
+                        });
+                        if is_declared_inout {
+                            debug!(
+                                "{} port {} declared inout, ignoring conflicting directions",
+                                self.name, c.vip_port
+                            );
+                        } else {
+                            let declaration = match port {
+                                Some(p) if !p.properties.dimensions.is_empty() => format!(
+                                    "{} inout {}",
+                                    p.name,
+                                    p.properties.get_dimensions_str()
+                                ),
+                                _ => format!("{} inout", c.vip_port),
+                            };
+                            errors.push(PortDirectionError::ConflictingDirections {
+                                vip: self.name.clone(),
+                                port: c.vip_port.clone(),
+                                first: first.to_string(),
+                                second: inference.to_string(),
+                                declaration,
+                            });
+                        }
+                    }
+                    Some(_first) => {
+                        debug!("{}", inference);
+                    }
+                    None => {
+                        debug!("{}", inference);
+                        directions.insert(c.vip_port.clone(), inference);
+                    }
                 }
             }
-            3 => {
-                if split[0] == "rand" {
-                    Ok(Member {
-                        name: split[2].to_string(),
-                        kind: split[1].to_string(),
-                        is_randomized: true,
-                    })
-                } else {
-                    Err(Self::Err::RandNotFound(s.to_string()))
+        }
+
+        for p in &mut self.ports {
+            let dir = directions
+                .get(&p.name)
+                .map(|inference| &inference.direction);
+            match (dir, p.is_direction_set) {
+                (Some(dir), false) => {
+                    debug!("port {} direction set to {:#?}", p.name, dir);
+                    p.properties.direction = dir.clone();
+                }
+                (Some(dir), true) => {
+                    if p.properties.direction == *dir {
+                        debug!("port {} direction matching dut ({:#?})", p.name, dir);
+                    } else if p.properties.direction == PortDirection::INOUT {
+                        debug!("port {} direction kept to declared INOUT", p.name);
+                    } else {
+                        errors.push(PortDirectionError::DeclaredDirectionMismatch {
+                            vip: self.name.clone(),
+                            port: p.name.clone(),
+                            declared: p.properties.direction.clone(),
+                            inference: directions[&p.name].to_string(),
+                            direction: dir.clone(),
+                        });
+                        p.properties.direction = dir.clone();
+                    }
+                }
+                (None, true) => {
+                    debug!(
+                        "port {} direction set to declared {:#?}",
+                        p.name, p.properties.direction
+                    );
+                }
+                (None, false) => {
+                    warn!("port {} direction not set", p.name);
                 }
             }
-            _ => Err(Self::Err::InvalidMemberDescription(s.to_string())),
         }
+        errors
     }
 }
 
+fn get_connection_direction(connection: &str, dut: &DUT) -> Option<PortDirection> {
+    let expr = connection.parse::<ConnectionExpr>().ok()?;
+    expr.terms.iter().find_map(|t| match t {
+        ConnectionTerm::Port { name, range: _ } => dut.ports.get(name).map(|p| p.direction.clone()),
+        ConnectionTerm::Constant { value: _, width: _ } => None,
+    })
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Item {
+    pub members: Vec<Member>,
+    pub constraints: Vec<Constraint>,
+    pub enums: Vec<EnumType>,
+}
+
 #[cfg(test)]
 mod tests {
-    use super::Member;
+    use std::collections::HashMap;
+
+    use super::{get_render_vips, PortDirectionError, VIPPort};
+    use crate::config::instance::Instances;
+    use crate::config::vip::VIP as VIPcfg;
+    use crate::dut::utils::{PortDirection, PortProperties, DUT};
 
     #[test]
-    fn successful_members_parsing() {
+    fn successful_vip_ports_parsing() {
         let descriptions = vec![
-            "__type__ __name__",
-            "rand __type__ __name__",
-            "bit is_valid",
-            "rand bit[24:16] is_ready",
-            "rand bit is_ready",
-            "  rand   bit   is_ready  ",
+            ("port_name", PortDirection::INOUT, false),
+            ("data [15:0]", PortDirection::INOUT, false),
+            ("data_vld out", PortDirection::OUTPUT, true),
+            ("data_vld out [0:0]", PortDirection::OUTPUT, true),
+            ("  data_rdy   in  ", PortDirection::INPUT, true),
+            ("data inout [7:0] [3:0]", PortDirection::INOUT, true),
+            ("data output [15:0]", PortDirection::OUTPUT, true),
         ];
 
-        for d in descriptions {
-            let parsed_member = d.parse::<Member>();
-            assert!(parsed_member.is_ok());
+        for (d, direction, is_direction_set) in descriptions {
+            let parsed_port = d.parse::<VIPPort>().unwrap();
+            assert_eq!(parsed_port.properties.direction, direction);
+            assert_eq!(parsed_port.is_direction_set, is_direction_set);
         }
     }
 
     #[test]
-    fn failed_members_parsing() {
+    fn failed_vip_ports_parsing() {
         let descriptions = vec![
             "",
-            "    ",
-            "__not_rand__ __type__ __name__",
-            "__name__",
-            "a b c d",
-            "rand bit [31:2] is_ready",
+            "data_vld outt",
+            "data [15:0] out",
+            "data out out [15:0]",
+            "data out [15:0] b",
         ];
 
         for d in descriptions {
-            let parsed_member = d.parse::<Member>();
-            assert!(parsed_member.is_err());
+            let parsed_port = d.parse::<VIPPort>();
+            assert!(parsed_port.is_err());
         }
     }
+
+    #[test]
+    fn conflicting_port_directions() {
+        let mut ports = HashMap::new();
+        for (name, direction) in [
+            ("data_in", PortDirection::INPUT),
+            ("data_out", PortDirection::OUTPUT),
+        ] {
+            let properties = PortProperties {
+                direction,
+                dimensions: vec![(15, 0)],
+            };
+            ports.insert(name.to_string(), properties);
+        }
+        let dut = DUT {
+            name: "fifo".to_string(),
+            ports,
+        };
+        let mut instances: Instances = toml::from_str(
+            r#"
+            [[instances]]
+            vip_name = "fifo"
+            connected_to = ["data_in"]
+
+            [[instances]]
+            vip_name = "fifo"
+            connected_to = ["data_out"]
+            "#,
+        )
+        .unwrap();
+        instances.estimate_ids();
+
+        let errors = |port: &str| {
+            let cfg: VIPcfg =
+                toml::from_str(&format!("name = \"fifo\"\nports = [\"{}\"]", port)).unwrap();
+            let mut vips = get_render_vips(&vec![cfg]);
+            let mut instances = instances.clone();
+            instances.set_connections(&vips, false);
+            vips[0].set_ports_dir(&instances, &dut)
+        };
+
+        // a controller drives data_in and receives data_out
+        let conflicts = errors("data [15:0]");
+        assert!(matches!(
+            &conflicts[..],
+            [PortDirectionError::ConflictingDirections { vip, port, declaration, .. }]
+                if vip == "fifo" && port == "data" && declaration == "data inout [15:0]"
+        ));
+        let message = conflicts[0].to_string();
+        assert!(
+            message.contains("dut input data_in implies output"),
+            "{}",
+            message
+        );
+        assert!(
+            message.contains("dut output data_out implies input"),
+            "{}",
+            message
+        );
+
+        assert!(errors("data inout [15:0]").is_empty());
+    }
 }